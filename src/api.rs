@@ -0,0 +1,64 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::types::*;
+
+// 抓取所需的网易云接口。NeteaseMusicClient 通过 Transport 实现它，
+// 也可以用其他数据来源实现，爬虫、关系图抓取和命令行都只依赖这个 trait
+#[async_trait]
+pub trait MusicApi: Send + Sync {
+    // 手机号密码登录，成功后记录 cookie
+    async fn login_cellphone(&mut self, phone: &str, password: &str) -> Result<LoginResponse>;
+
+    async fn get_qr_key(&self) -> Result<String>;
+
+    async fn check_qr(&self, key: &str) -> Result<QrCheckResponse>;
+
+    fn set_cookie(&mut self, cookie: String);
+
+    fn cookie(&self) -> Option<&str>;
+
+    async fn get_user_profile(&self, uid: i64) -> Result<UserProfile>;
+
+    async fn get_user_record(&self, uid: i64) -> Result<UserRecord>;
+
+    async fn get_user_playlists(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<PlaylistResponse>;
+
+    async fn get_user_follows(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<FollowsResponse>;
+
+    async fn get_user_followeds(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<FollowedsResponse>;
+
+    async fn get_playlist_tracks(&self, playlist_id: i64, limit: Option<i32>, offset: Option<i32>) -> Result<PlaylistTracksResponse>;
+
+    // 一批歌曲的详情，一次最多 metadata::SONG_DETAIL_BATCH 首
+    async fn get_song_detail(&self, ids: &[i64]) -> Result<SongDetailResponse>;
+
+    // before 为上一页最后一条评论的时间，超过 5000 条评论时用于翻页
+    async fn get_song_comments(&self, song_id: i64, limit: i32, offset: i32, before: Option<i64>) -> Result<CommentResponse>;
+
+    // 任意资源的评论，参数与 get_song_comments 相同。默认只支持歌曲，其他资源需要实现方覆盖
+    async fn get_comments(&self, resource: ResourceType, id: i64, limit: i32, offset: i32, before: Option<i64>) -> Result<CommentResponse> {
+        match resource {
+            ResourceType::Song => self.get_song_comments(id, limit, offset, before).await,
+            other => Err(anyhow::anyhow!("不支持获取{}的评论", other.label())),
+        }
+    }
+
+    // 各类资源的热门评论，按点赞数排序
+    async fn get_hot_comments(&self, resource: ResourceType, id: i64, limit: i32, offset: i32) -> Result<HotCommentsResponse>;
+}
+
+// 命令行可选的数据来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum Backend {
+    // 经由 NeteaseCloudMusicApi 部署访问
+    #[default]
+    ProxyApi,
+    // 自行加密后直接访问 music.163.com
+    Direct,
+    // 只读本地响应缓存
+    Cache,
+    // 使用本地模拟数据
+    Mock,
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use netease_music_api::mock_server::{self, MockServerConfig};
+
+#[derive(Parser)]
+#[command(about = "本地模拟 NeteaseCloudMusicApi 服务，从 fixture 文件返回响应")]
+struct Args {
+    #[arg(long, default_value = "127.0.0.1:3000", help = "监听地址")]
+    addr: SocketAddr,
+    #[arg(long, default_value = "tests/fixtures/mock", help = "模拟数据目录")]
+    fixtures: PathBuf,
+    #[arg(long, default_value_t = 0, help = "每个请求的延迟（毫秒）")]
+    latency_ms: u64,
+    #[arg(long, help = "每 N 个请求返回一次错误")]
+    fail_every: Option<u64>,
+    #[arg(long, default_value_t = 502, help = "注入错误时返回的 code")]
+    error_code: i32,
+    #[arg(long, help = "每秒最多处理的请求数，超出返回 405")]
+    rate_limit: Option<u32>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = MockServerConfig {
+        latency: Duration::from_millis(args.latency_ms),
+        fail_every: args.fail_every,
+        error_code: args.error_code,
+        rate_limit: args.rate_limit,
+        ..MockServerConfig::new(args.fixtures)
+    };
+
+    let addr = mock_server::spawn(config, args.addr).await?;
+    println!("模拟服务已启动: http://{}", addr);
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::output;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+
+// 不参与缓存键计算的查询参数
+const VOLATILE_PARAMS: &[&str] = &["timestamp"];
+
+// 响应取决于登录账号的接口（私密歌单、只对本人可见的听歌榜单等），缓存键中加入账号
+const ACCOUNT_SCOPED: &[&str] = &["/user/record", "/user/playlist", "/playlist/track/all"];
+
+// 各接口默认缓存时间，未列出的接口不缓存。/login/ 下的接口即使设置了也不缓存
+const DEFAULT_TTLS: &[(&str, u64)] = &[
+    ("/user/detail", 60 * 60),
+    ("/user/record", 60 * 60),
+    ("/user/playlist", 60 * 60),
+    ("/user/follows", 6 * 60 * 60),
+    ("/user/followeds", 6 * 60 * 60),
+    ("/playlist/track/all", 60 * 60),
+    ("/song/detail", 7 * 24 * 60 * 60),
+    ("/comment/music", 24 * 60 * 60),
+    ("/comment/playlist", 24 * 60 * 60),
+    ("/comment/album", 24 * 60 * 60),
+    ("/comment/mv", 24 * 60 * 60),
+    ("/comment/video", 24 * 60 * 60),
+    ("/comment/dj", 24 * 60 * 60),
+    ("/comment/event", 24 * 60 * 60),
+    ("/comment/hot", 24 * 60 * 60),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub key: String,
+    pub path: String,
+    // 写入时间（Unix 秒）
+    pub stored_at: u64,
+    pub status: u16,
+    pub body: String,
+}
+
+impl CacheEntry {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_secs().saturating_sub(self.stored_at))
+    }
+}
+
+// 单个接口的缓存统计
+#[derive(Debug, Default, Clone)]
+pub struct EndpointStats {
+    pub entries: usize,
+    pub expired: usize,
+    pub bytes: u64,
+}
+
+// 磁盘上的响应缓存，每个请求一个 JSON 文件
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttls: HashMap<String, Duration>,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            ttls: DEFAULT_TTLS
+                .iter()
+                .map(|(path, secs)| (path.to_string(), Duration::from_secs(*secs)))
+                .collect(),
+        }
+    }
+
+    // 设置某个接口的缓存时间，传入 0 表示不缓存该接口
+    pub fn set_ttl(&mut self, path: &str, ttl: Duration) {
+        self.ttls.insert(path.to_string(), ttl);
+    }
+
+    pub fn ttl(&self, path: &str) -> Option<Duration> {
+        if path.starts_with("/login/") {
+            return None;
+        }
+        self.ttls.get(path).copied().filter(|ttl| !ttl.is_zero())
+    }
+
+    fn file_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", fnv1a(key)))
+    }
+
+    // 读取缓存项，ignore_ttl 为 true 时过期的缓存也会返回
+    pub fn get(&self, request: &HttpRequest, ignore_ttl: bool) -> Option<CacheEntry> {
+        let key = cache_key(request);
+        let entry: CacheEntry = serde_json::from_str(&fs::read_to_string(self.file_for(&key)).ok()?).ok()?;
+        if entry.key != key {
+            return None;
+        }
+        if !ignore_ttl && !self.is_fresh(&entry) {
+            return None;
+        }
+        Some(entry)
+    }
+
+    fn is_fresh(&self, entry: &CacheEntry) -> bool {
+        self.ttl(&entry.path).is_some_and(|ttl| entry.age() < ttl)
+    }
+
+    pub fn put(&self, request: &HttpRequest, response: &HttpResponse) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("无法创建缓存目录 {}", self.dir.display()))?;
+        let key = cache_key(request);
+        let entry = CacheEntry {
+            path: request.path.clone(),
+            stored_at: now_secs(),
+            status: response.status,
+            body: String::from_utf8_lossy(&response.body).into_owned(),
+            key,
+        };
+        // 经过临时文件改名写入，中断或并发读取时不会看到写了一半的缓存项
+        output::write_atomic(&self.file_for(&entry.key), serde_json::to_string(&entry)?)
+    }
+
+    // 遍历所有缓存项，无法解析的文件会被跳过
+    pub fn entries(&self) -> Result<Vec<(PathBuf, CacheEntry)>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for file in fs::read_dir(&self.dir)? {
+            let file = file?.path();
+            if file.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            if let Some(entry) = fs::read_to_string(&file)
+                .ok()
+                .and_then(|s| serde_json::from_str::<CacheEntry>(&s).ok())
+            {
+                entries.push((file, entry));
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn stats(&self) -> Result<HashMap<String, EndpointStats>> {
+        let mut stats: HashMap<String, EndpointStats> = HashMap::new();
+        for (file, entry) in self.entries()? {
+            let item = stats.entry(entry.path.clone()).or_default();
+            item.entries += 1;
+            item.bytes += fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+            if !self.is_fresh(&entry) {
+                item.expired += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    // 删除缓存项，返回删除的数量
+    pub fn purge(&self, only_expired: bool, endpoint: Option<&str>) -> Result<usize> {
+        let mut removed = 0;
+        for (file, entry) in self.entries()? {
+            if endpoint.is_some_and(|path| path != entry.path) {
+                continue;
+            }
+            if only_expired && self.is_fresh(&entry) {
+                continue;
+            }
+            fs::remove_file(&file)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+// 先查缓存再请求的传输层。inner 为 None 时只读缓存（离线模式）
+pub struct CachingTransport {
+    inner: Option<Arc<dyn Transport>>,
+    cache: ResponseCache,
+}
+
+impl CachingTransport {
+    pub fn new(inner: Arc<dyn Transport>, cache: ResponseCache) -> Self {
+        Self {
+            inner: Some(inner),
+            cache,
+        }
+    }
+
+    // 离线模式：忽略缓存时间，未命中时直接报错
+    pub fn offline(cache: ResponseCache) -> Self {
+        Self { inner: None, cache }
+    }
+}
+
+#[async_trait]
+impl Transport for CachingTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let Some(inner) = &self.inner else {
+            let entry = self
+                .cache
+                .get(&request, true)
+                .ok_or_else(|| anyhow::anyhow!("离线模式下缓存中没有 {}", request.key(VOLATILE_PARAMS)))?;
+            return Ok(entry.into());
+        };
+
+        if self.cache.ttl(&request.path).is_none() {
+            return inner.send(request).await;
+        }
+        if let Some(entry) = self.cache.get(&request, false) {
+            return Ok(entry.into());
+        }
+
+        let response = inner.send(request.clone()).await?;
+        if is_cacheable(&response) {
+            if let Err(e) = self.cache.put(&request, &response) {
+                tracing::warn!(path = %request.path, error = %e, "写入缓存失败");
+            }
+        }
+        Ok(response)
+    }
+}
+
+impl From<CacheEntry> for HttpResponse {
+    fn from(entry: CacheEntry) -> Self {
+        HttpResponse {
+            status: entry.status,
+            set_cookies: Vec::new(),
+            body: entry.body.into_bytes(),
+        }
+    }
+}
+
+// 只缓存成功的响应，限流和错误响应不写入缓存
+fn is_cacheable(response: &HttpResponse) -> bool {
+    response.status == 200
+        && serde_json::from_slice::<serde_json::Value>(&response.body)
+            .ok()
+            .and_then(|body| body.get("code").and_then(|c| c.as_i64()))
+            == Some(200)
+}
+
+// 路径加查询参数；ACCOUNT_SCOPED 中的接口再加上 MUSIC_U 的哈希，未登录时为 anonymous，
+// 避免不同账号或未登录时读到别人的私密数据
+fn cache_key(request: &HttpRequest) -> String {
+    let key = request.key(VOLATILE_PARAMS);
+    if !ACCOUNT_SCOPED.contains(&request.path.as_str()) {
+        return key;
+    }
+    let account = request
+        .cookie
+        .as_deref()
+        .into_iter()
+        .flat_map(|cookie| cookie.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, value)| *name == "MUSIC_U" && !value.is_empty())
+        .map_or_else(|| "anonymous".to_string(), |(_, value)| format!("{:016x}", fnv1a(value)));
+    format!("{}#account={}", key, account)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// 缓存文件名使用稳定的 FNV-1a 哈希，保证不同版本之间文件名一致
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,222 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::transport::{request_key, HttpRequest, HttpResponse, Transport};
+
+const REDACTED: &str = "REDACTED";
+
+// 录制时忽略、回放时不参与匹配的查询参数
+const VOLATILE_PARAMS: &[&str] = &["timestamp"];
+
+// 需要脱敏的查询参数和响应字段
+const SECRET_PARAMS: &[&str] = &["phone", "password", "md5_password", "captcha"];
+const SECRET_FIELDS: &[&str] = &["cookie", "token"];
+
+// 一次请求/响应记录，保存为 cassette 目录下的一个 JSON 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub cookie: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub set_cookies: Vec<String>,
+    // 能解析为 JSON 时保存为 JSON，否则保存为字符串
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    fn redacted(request: &HttpRequest) -> Self {
+        Self {
+            path: request.path.clone(),
+            query: request
+                .query
+                .iter()
+                .map(|(k, v)| {
+                    let v = if SECRET_PARAMS.contains(&k.as_str()) { REDACTED } else { v };
+                    (k.clone(), v.to_string())
+                })
+                .collect(),
+            cookie: request.cookie.as_ref().map(|_| REDACTED.to_string()),
+        }
+    }
+}
+
+impl RecordedResponse {
+    fn redacted(response: &HttpResponse) -> Self {
+        let mut body = serde_json::from_slice(&response.body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&response.body).into_owned()));
+        redact_fields(&mut body);
+        Self {
+            status: response.status,
+            set_cookies: response.set_cookies.iter().map(|c| redact_set_cookie(c)).collect(),
+            body,
+        }
+    }
+
+    fn to_response(&self) -> HttpResponse {
+        let body = match &self.body {
+            Value::String(text) => text.clone().into_bytes(),
+            other => other.to_string().into_bytes(),
+        };
+        HttpResponse {
+            status: self.status,
+            set_cookies: self.set_cookies.clone(),
+            body,
+        }
+    }
+}
+
+// MUSIC_U=xxx; Path=/ 只保留名称和属性
+fn redact_set_cookie(cookie: &str) -> String {
+    let mut parts = cookie.splitn(2, ';');
+    let pair = parts.next().unwrap_or_default();
+    let name = pair.split('=').next().unwrap_or_default();
+    match parts.next() {
+        Some(attrs) => format!("{}={};{}", name, REDACTED, attrs),
+        None => format!("{}={}", name, REDACTED),
+    }
+}
+
+fn redact_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if SECRET_FIELDS.contains(&key.as_str()) && field.is_string() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_fields),
+        _ => {}
+    }
+}
+
+// 回放时用于匹配请求的键，时间戳和敏感参数不参与匹配
+fn match_key(path: &str, query: &[(String, String)]) -> String {
+    let skip: Vec<&str> = VOLATILE_PARAMS.iter().chain(SECRET_PARAMS).copied().collect();
+    request_key(path, query, &skip)
+}
+
+// 录制文件名开头的编号，例如 00012_comment_music.json 为 12，不是录制文件时为 None
+fn interaction_index(path: &Path) -> Option<usize> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    let (index, _) = path.file_stem()?.to_str()?.split_once('_')?;
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+// 目录下的录制文件，按编号排序。编号超过五位时文件名不再按字典序排列，所以按数值排序
+fn interaction_files(dir: &Path) -> Result<Vec<(usize, PathBuf)>> {
+    let mut files: Vec<(usize, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter_map(|path| Some((interaction_index(&path)?, path)))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// 包装另一个传输层，把每次请求/响应脱敏后写入 cassette 目录
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    dir: PathBuf,
+    counter: AtomicUsize,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<dyn Transport>, dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).with_context(|| format!("无法创建录制目录 {}", dir.display()))?;
+        // 在已有录制的最大编号之后继续编号，目录中的其他文件不影响编号
+        let next = interaction_files(&dir)?.last().map_or(0, |(index, _)| index + 1);
+        Ok(Self {
+            inner,
+            dir,
+            counter: AtomicUsize::new(next),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let recorded_request = RecordedRequest::redacted(&request);
+        let response = self.inner.send(request).await?;
+
+        let interaction = Interaction {
+            request: recorded_request,
+            response: RecordedResponse::redacted(&response),
+        };
+        let index = self.counter.fetch_add(1, Ordering::SeqCst);
+        let slug = interaction.request.path.trim_matches('/').replace('/', "_");
+        let file = self.dir.join(format!("{:05}_{}.json", index, slug));
+        fs::write(&file, serde_json::to_string_pretty(&interaction)?)?;
+
+        Ok(response)
+    }
+}
+
+// 从 cassette 目录回放录制的响应，不访问网络
+pub struct ReplayTransport {
+    // 同一请求录制了多次时按录制顺序返回，最后一个重复使用
+    interactions: Mutex<HashMap<String, VecDeque<RecordedResponse>>>,
+}
+
+impl ReplayTransport {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let files = interaction_files(dir).with_context(|| format!("无法读取回放目录 {}", dir.display()))?;
+        let mut interactions: HashMap<String, VecDeque<RecordedResponse>> = HashMap::new();
+        for (_, file) in files {
+            let interaction: Interaction = serde_json::from_str(&fs::read_to_string(&file)?)
+                .with_context(|| format!("无法解析录制文件 {}", file.display()))?;
+            interactions
+                .entry(match_key(&interaction.request.path, &interaction.request.query))
+                .or_default()
+                .push_back(interaction.response);
+        }
+        Ok(Self {
+            interactions: Mutex::new(interactions),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let key = match_key(&request.path, &request.query);
+        let mut interactions = self.interactions.lock().unwrap();
+        let queue = interactions
+            .get_mut(&key)
+            .ok_or_else(|| anyhow::anyhow!("录制中没有请求 {}", key))?;
+        let response = if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        };
+        response
+            .map(|r| r.to_response())
+            .ok_or_else(|| anyhow::anyhow!("录制中没有请求 {}", key))
+    }
+}
@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use qr2term::print_qr;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::Instrument;
+
+use crate::api::MusicApi;
+use crate::transport::{HttpRequest, HttpResponse, ReqwestTransport, Transport};
+use crate::types::*;
+
+pub struct NeteaseMusicClient {
+    transport: Arc<dyn Transport>,
+    cookie: Option<String>,
+}
+
+impl NeteaseMusicClient {
+    pub fn new() -> Self {
+        Self::with_transport(Arc::new(ReqwestTransport::default()))
+    }
+
+    // 使用自定义传输层，例如测试中的 MockTransport
+    pub fn with_transport(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            cookie: None,
+        }
+    }
+
+    async fn request(&self, path: &str, query: &[(&str, String)]) -> Result<HttpResponse> {
+        let request = HttpRequest {
+            path: path.to_string(),
+            query: query.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+            cookie: self.cookie.clone(),
+        };
+        // 所有评论接口都记录资源 ID（动态为 threadId），歌曲评论另外记录 song_id
+        let resource_id = if path.starts_with("/comment/") {
+            request.query_value("id").or_else(|| request.query_value("threadId"))
+        } else {
+            None
+        };
+        let is_song = path == "/comment/music" || (path == "/comment/hot" && request.query_value("type") == Some("0"));
+        let song_id = if is_song { resource_id } else { None };
+        let span = tracing::debug_span!(
+            "request",
+            path,
+            uid = request.query_value("uid"),
+            resource_id,
+            song_id,
+            offset = request.query_value("offset"),
+            before = request.query_value("before"),
+        );
+
+        async {
+            let started = Instant::now();
+            let result = self.transport.send(request).await;
+            let latency_ms = started.elapsed().as_millis() as u64;
+            match &result {
+                Ok(response) => {
+                    // 只在需要时解析响应体中的 code
+                    let code = tracing::enabled!(tracing::Level::DEBUG)
+                        .then(|| serde_json::from_slice::<serde_json::Value>(&response.body).ok())
+                        .flatten()
+                        .and_then(|body| body.get("code").and_then(|c| c.as_i64()));
+                    tracing::debug!(status = response.status, code, latency_ms, "请求完成")
+                }
+                Err(e) => tracing::warn!(latency_ms, error = %e, "请求失败"),
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let response = self.request(path, query).await?;
+        serde_json::from_slice(&response.body)
+            .with_context(|| format!("解析 {} 的响应失败（HTTP {}）", path, response.status))
+    }
+
+    pub async fn login(&mut self, phone: &str, password: &str) -> Result<()> {
+        let response_with_cookie = self.login_cellphone(phone, password).await?;
+        fs::write("login_info.json", serde_json::to_string(&response_with_cookie)?)?;
+        tracing::info!("登录成功");
+        Ok(())
+    }
+
+    // 关注/取消关注用户
+    pub async fn follow_user(&self, uid: i64, follow: bool) -> Result<serde_json::Value> {
+        self.get_json::<serde_json::Value>(
+            "/follow",
+            &[
+                ("id", uid.to_string()),
+                ("t", if follow { "1" } else { "0" }.to_string()),
+            ],
+        )
+        .await
+    }
+
+    // 生成二维码
+    pub async fn create_qr(&self, key: &str) -> Result<String> {
+        let timestamp = chrono::Local::now().timestamp_millis().to_string();
+        let response: QrCreateResponse = self
+            .get_json(
+                "/login/qr/create",
+                &[
+                    ("key", key.to_string()),
+                    ("qrimg", "true".to_string()),
+                    ("timestamp", timestamp),
+                ],
+            )
+            .await?;
+
+        if response.code == 200 {
+            if let Some(qr_img) = response.data.qrimg {
+                // 使用新的 base64 解码方法
+                let qr_data = BASE64_STANDARD.decode(
+                    qr_img.split(',').nth(1).unwrap_or("")
+                )?;
+                
+                // 克隆数据用于文件写入
+                fs::write("qr_code.png", &qr_data)?;
+                println!("\n二维码已保存到 qr_code.png");
+            }
+
+            // 直接打印 URL 的二维码，直连模式下没有二维码图片
+            print_qr(response.data.qrurl.as_bytes())?;
+
+            Ok(response.data.qrurl)
+        } else {
+            Err(anyhow::anyhow!("生成二维码失败"))
+        }
+    }
+
+    // 二维码登录流程
+    pub async fn login_by_qr(&mut self) -> Result<()> {
+        tracing::info!("开始二维码登录流程");
+        
+        // 获取二维码 key
+        let key = self.get_qr_key().await?;
+        
+        // 生成二维码
+        let qr_img = self.create_qr(&key).await?;
+        println!("\n请使用网易云音乐 App 扫描二维码：\n{}", qr_img);
+        
+        let cookie = self.poll_qr_login(&key).await?;
+
+        // 保存登录信息
+        let login_info = LoginResponse {
+            code: 200,
+            cookie,
+            token: String::new(),
+            account: None,
+            profile: None,
+        };
+        fs::write("login_info.json", serde_json::to_string(&login_info)?)?;
+        Ok(())
+    }
+
+    // 每 2 秒检查一次扫码状态，直到登录成功（返回 cookie）或二维码过期
+    pub async fn poll_qr_login(&mut self, key: &str) -> Result<String> {
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            let check_resp = self.check_qr(key).await?;
+            
+            match check_resp.code {
+                800 => {
+                    tracing::error!("二维码已过期，请重新运行程序");
+                    return Err(anyhow::anyhow!("二维码已过期"));
+                }
+                801 => {
+                    tracing::info!("等待扫码中");
+                }
+                802 => {
+                    tracing::info!("扫码成功，请在手机上确认登录");
+                }
+                803 => {
+                    tracing::info!("登录成功");
+                    if let Some(cookie) = check_resp.cookie {
+                        self.cookie = Some(cookie.clone());
+                        return Ok(cookie);
+                    }
+                }
+                _ => {
+                    tracing::warn!(code = check_resp.code, message = %check_resp.message, "未知的扫码状态");
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MusicApi for NeteaseMusicClient {
+    // 使用已保存的登录 cookie
+    fn set_cookie(&mut self, cookie: String) {
+        self.cookie = Some(cookie);
+    }
+
+    fn cookie(&self) -> Option<&str> {
+        self.cookie.as_deref()
+    }
+
+    // 手机号密码登录，成功后记录 cookie 并返回登录信息
+    async fn login_cellphone(&mut self, phone: &str, password: &str) -> Result<LoginResponse> {
+        let response = self
+            .request(
+                "/login/cellphone",
+                &[("phone", phone.to_string()), ("password", password.to_string())],
+            )
+            .await?;
+        let response_data: LoginResponse = serde_json::from_slice(&response.body)?;
+
+        if response_data.code == 200 {
+            let cookie = response.set_cookies.join("; ");
+            self.cookie = Some(cookie.clone());
+            Ok(LoginResponse {
+                cookie,
+                ..response_data
+            })
+        } else {
+            Err(anyhow::anyhow!("登录失败：状态码 {}", response_data.code))
+        }
+    }
+
+    // 获取二维码 key
+    async fn get_qr_key(&self) -> Result<String> {
+        let timestamp = chrono::Local::now().timestamp_millis().to_string();
+        let response: QrKeyResponse = self
+            .get_json("/login/qr/key", &[("timestamp", timestamp)])
+            .await?;
+
+        if response.code == 200 {
+            Ok(response.key_data.key)
+        } else {
+            Err(anyhow::anyhow!("获取二维码key失败"))
+        }
+    }
+
+    // 检查二维码状态
+    async fn check_qr(&self, key: &str) -> Result<QrCheckResponse> {
+        let timestamp = chrono::Local::now().timestamp_millis().to_string();
+        self.get_json(
+            "/login/qr/check",
+            &[("key", key.to_string()), ("timestamp", timestamp)],
+        )
+        .await
+    }
+
+    async fn get_user_profile(&self, uid: i64) -> Result<UserProfile> {
+        self.get_json::<UserProfile>("/user/detail", &[("uid", uid.to_string())]).await
+    }
+
+    async fn get_user_record(&self, uid: i64) -> Result<UserRecord> {
+        self.get_json::<UserRecord>(
+            "/user/record",
+            &[("uid", uid.to_string()), ("type", "0".to_string())],
+        )
+        .await
+    }
+
+    // 获取用户歌单
+    async fn get_user_playlists(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<PlaylistResponse> {
+        self.get_json::<PlaylistResponse>(
+            "/user/playlist",
+            &[
+                ("uid", uid.to_string()),
+                ("limit", limit.unwrap_or(30).to_string()),
+                ("offset", offset.unwrap_or(0).to_string()),
+            ],
+        )
+        .await
+    }
+
+    // 获取用户关注列表
+    async fn get_user_follows(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<FollowsResponse> {
+        self.get_json::<FollowsResponse>(
+            "/user/follows",
+            &[
+                ("uid", uid.to_string()),
+                ("limit", limit.unwrap_or(30).to_string()),
+                ("offset", offset.unwrap_or(0).to_string()),
+            ],
+        )
+        .await
+    }
+
+    // 获取用户粉丝列表
+    async fn get_user_followeds(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<FollowedsResponse> {
+        self.get_json::<FollowedsResponse>(
+            "/user/followeds",
+            &[
+                ("uid", uid.to_string()),
+                ("limit", limit.unwrap_or(30).to_string()),
+                ("offset", offset.unwrap_or(0).to_string()),
+            ],
+        )
+        .await
+    }
+
+    // 获取歌单中的歌曲
+    async fn get_playlist_tracks(&self, playlist_id: i64, limit: Option<i32>, offset: Option<i32>) -> Result<PlaylistTracksResponse> {
+        self.get_json::<PlaylistTracksResponse>(
+            "/playlist/track/all",
+            &[
+                ("id", playlist_id.to_string()),
+                ("limit", limit.unwrap_or(1000).to_string()),
+                ("offset", offset.unwrap_or(0).to_string()),
+            ],
+        )
+        .await
+    }
+
+    // 获取歌曲详情
+    async fn get_song_detail(&self, ids: &[i64]) -> Result<SongDetailResponse> {
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        self.get_json::<SongDetailResponse>("/song/detail", &[("ids", ids.join(","))]).await
+    }
+
+    // 获取歌曲评论
+    // before 为上一页最后一条评论的时间，超过 5000 条评论时用于翻页
+    async fn get_song_comments(&self, song_id: i64, limit: i32, offset: i32, before: Option<i64>) -> Result<CommentResponse> {
+        self.get_comments(ResourceType::Song, song_id, limit, offset, before).await
+    }
+
+    // 获取任意资源的评论，动态的评论接口使用 threadId 而不是 id
+    async fn get_comments(&self, resource: ResourceType, id: i64, limit: i32, offset: i32, before: Option<i64>) -> Result<CommentResponse> {
+        let id_param = match resource {
+            ResourceType::Event { .. } => ("threadId", resource.thread_id(id)),
+            _ => ("id", id.to_string()),
+        };
+        let mut query = vec![id_param, ("limit", limit.to_string()), ("offset", offset.to_string())];
+        if let Some(before) = before {
+            query.push(("before", before.to_string()));
+        }
+        self.get_json::<CommentResponse>(resource.comment_path(), &query).await
+    }
+
+    // 获取热门评论
+    async fn get_hot_comments(&self, resource: ResourceType, id: i64, limit: i32, offset: i32) -> Result<HotCommentsResponse> {
+        self.get_json::<HotCommentsResponse>(
+            "/comment/hot",
+            &[
+                ("id", resource.query_id(id)),
+                ("type", resource.code().to_string()),
+                ("limit", limit.to_string()),
+                ("offset", offset.to_string()),
+            ],
+        )
+        .await
+    }
+}
+
+impl Default for NeteaseMusicClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 为 NeteaseMusicClient 实现 Clone
+impl Clone for NeteaseMusicClient {
+    fn clone(&self) -> Self {
+        Self {
+            transport: self.transport.clone(),
+            cookie: self.cookie.clone(),
+        }
+    }
+}
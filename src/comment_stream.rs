@@ -0,0 +1,271 @@
+use anyhow::Result;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+
+use crate::api::MusicApi;
+use crate::types::{Comment, CommentResponse, ResourceType};
+use crate::REQUEST_DELAY_MS;
+
+// 超过这个偏移量后接口要求改用 before 游标分页
+const MAX_OFFSET_PAGING: i32 = 5000;
+
+#[derive(Debug, Clone)]
+pub struct CommentStreamOptions {
+    // 评论所属的资源类型，comment_stream 的 id 是这类资源的 ID
+    pub resource: ResourceType,
+    // 每页评论数，接口上限为 100
+    pub page_size: i32,
+    // 单页请求失败后的重试次数
+    pub max_retries: u32,
+    // 第 n 次重试前等待 n * retry_delay
+    pub retry_delay: Duration,
+    // 最多读取的评论数，None 表示读到末尾
+    pub max_comments: Option<usize>,
+    // 起始分页位置，用于从上次失败处继续
+    pub start: PageCursor,
+    // 传入后记录请求数和成功读取的页数
+    pub stats: Option<Arc<StreamStats>>,
+}
+
+impl Default for CommentStreamOptions {
+    fn default() -> Self {
+        Self {
+            resource: ResourceType::Song,
+            page_size: 100,
+            max_retries: 3,
+            retry_delay: Duration::from_secs(1),
+            max_comments: None,
+            start: PageCursor::Offset(0),
+            stats: None,
+        }
+    }
+}
+
+// 评论流的运行统计
+#[derive(Debug, Default)]
+pub struct StreamStats {
+    // 发出的请求数，包括重试
+    pub requests: AtomicUsize,
+    // 成功读取的页数
+    pub pages: AtomicUsize,
+    // 最近一次请求的分页位置，流因错误结束时即为失败页的位置
+    pub cursor: Mutex<Option<PageCursor>>,
+    // 接口返回的评论总数，第一页成功后才有值
+    pub total: Mutex<Option<usize>>,
+}
+
+impl StreamStats {
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    pub fn pages(&self) -> usize {
+        self.pages.load(Ordering::Relaxed)
+    }
+
+    pub fn cursor(&self) -> Option<PageCursor> {
+        *self.cursor.lock().unwrap()
+    }
+
+    pub fn total(&self) -> Option<usize> {
+        *self.total.lock().unwrap()
+    }
+}
+
+// 分页位置：先按 offset 翻页，超过 5000 条后按上一页最后一条评论的时间翻页
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageCursor {
+    Offset(i32),
+    Before(i64),
+}
+
+struct PagingState {
+    id: i64,
+    cursor: PageCursor,
+    fetched: usize,
+    done: bool,
+}
+
+// 按游标获取一页评论，失败时按配置重试
+async fn fetch_comment_page<A: MusicApi + ?Sized>(
+    api: &A,
+    id: i64,
+    cursor: PageCursor,
+    options: &CommentStreamOptions,
+) -> Result<CommentResponse> {
+    let (offset, before) = match cursor {
+        PageCursor::Offset(offset) => (offset, None),
+        PageCursor::Before(time) => (0, Some(time)),
+    };
+    if let Some(stats) = &options.stats {
+        *stats.cursor.lock().unwrap() = Some(cursor);
+    }
+    let mut attempt = 0;
+    loop {
+        sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+        if let Some(stats) = &options.stats {
+            stats.requests.fetch_add(1, Ordering::Relaxed);
+        }
+        let result = api
+            .get_comments(options.resource, id, options.page_size, offset, before)
+            .await
+            .and_then(|response| {
+                if response.code == 200 {
+                    Ok(response)
+                } else {
+                    Err(anyhow::anyhow!("接口返回状态码 {}", response.code))
+                }
+            });
+        match result {
+            Ok(response) => {
+                if let Some(stats) = &options.stats {
+                    stats.pages.fetch_add(1, Ordering::Relaxed);
+                    if response.total > 0 {
+                        *stats.total.lock().unwrap() = Some(response.total as usize);
+                    }
+                }
+                return Ok(response);
+            }
+            Err(e) if attempt < options.max_retries => {
+                attempt += 1;
+                tracing::warn!(resource = %options.resource, id, offset, before, attempt, error = %e, "获取评论失败，准备重试");
+                sleep(options.retry_delay * attempt).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// 为所有 MusicApi 实现提供评论流
+pub trait CommentStreamExt: MusicApi {
+    // 以流的形式逐条返回资源（默认为歌曲，见 options.resource）下的评论，自动处理翻页、重试和结束判断。
+    // 重试耗尽后会产出一个 Err 并结束。
+    fn comment_stream(
+        &self,
+        id: i64,
+        options: CommentStreamOptions,
+    ) -> impl Stream<Item = Result<Comment>> + Send + '_ {
+        let state = PagingState {
+            id,
+            cursor: options.start,
+            fetched: 0,
+            done: false,
+        };
+
+        stream::unfold((state, options), move |(mut state, options)| async move {
+            if state.done {
+                return None;
+            }
+            if let Some(max) = options.max_comments {
+                if state.fetched >= max {
+                    return None;
+                }
+            }
+
+            let page = match fetch_comment_page(self, state.id, state.cursor, &options).await {
+                Ok(response) => response,
+                Err(e) => {
+                    state.done = true;
+                    return Some((vec![Err(e)], (state, options)));
+                }
+            };
+
+            let mut comments = page.comments;
+            let count = comments.len();
+            if let Some(max) = options.max_comments {
+                comments.truncate(max - state.fetched);
+            }
+            state.fetched += comments.len();
+
+            // 返回条数不足一页或接口标明没有更多时结束
+            if count < options.page_size as usize || page.more == Some(false) {
+                state.done = true;
+            } else {
+                state.cursor = next_cursor(state.cursor, options.page_size, comments.last());
+            }
+
+            Some((comments.into_iter().map(Ok).collect::<Vec<_>>(), (state, options)))
+        })
+        .flat_map(stream::iter)
+    }
+
+    // 分页读取任意资源的热门评论，最多 limit 条
+    fn hot_comments(
+        &self,
+        resource: ResourceType,
+        id: i64,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Comment>>> + Send + '_ {
+        const PAGE: usize = 50;
+        async move {
+            let mut comments = Vec::new();
+            while comments.len() < limit {
+                sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+                let page_size = PAGE.min(limit - comments.len()) as i32;
+                let page = self.get_hot_comments(resource, id, page_size, comments.len() as i32).await?;
+                if page.code != 200 {
+                    return Err(anyhow::anyhow!("获取热门评论失败，状态码 {}", page.code));
+                }
+                let count = page.hot_comments.len();
+                comments.extend(page.hot_comments);
+                if !page.has_more || count == 0 {
+                    break;
+                }
+            }
+            comments.truncate(limit);
+            Ok(comments)
+        }
+    }
+}
+
+impl<T: MusicApi + ?Sized> CommentStreamExt for T {}
+
+fn next_cursor(cursor: PageCursor, page_size: i32, last: Option<&Comment>) -> PageCursor {
+    match (cursor, last) {
+        (PageCursor::Offset(offset), _) if offset + page_size < MAX_OFFSET_PAGING => {
+            PageCursor::Offset(offset + page_size)
+        }
+        (_, Some(comment)) => PageCursor::Before(comment.time),
+        (cursor, None) => cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommentUser;
+
+    fn comment(time: i64) -> Comment {
+        Comment {
+            comment_id: 1,
+            user: CommentUser {
+                user_id: 1,
+                nickname: String::new(),
+                avatar_url: String::new(),
+            },
+            content: String::new(),
+            time,
+            liked_count: 0,
+            be_replied: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn switches_to_before_cursor_past_offset_limit() {
+        let last = comment(1_700_000_000_000);
+        assert_eq!(next_cursor(PageCursor::Offset(0), 100, Some(&last)), PageCursor::Offset(100));
+        assert_eq!(
+            next_cursor(PageCursor::Offset(4900), 100, Some(&last)),
+            PageCursor::Before(1_700_000_000_000)
+        );
+        assert_eq!(
+            next_cursor(PageCursor::Before(1), 100, Some(&last)),
+            PageCursor::Before(1_700_000_000_000)
+        );
+    }
+}
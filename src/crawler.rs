@@ -0,0 +1,848 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use indicatif::ProgressBar;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::oneshot;
+
+use crate::api::MusicApi;
+use crate::comment_stream::{CommentStreamExt, CommentStreamOptions, PageCursor, StreamStats};
+use crate::filter::Filter;
+use crate::mentions::{self, MentionKind, MentionOutput, MentionTarget};
+use crate::metadata;
+use crate::output::{self, BoundedWriter, JsonArrayWriter};
+use crate::progress::{CrawlProgress, ProgressMode};
+use crate::report::{CrawlReport, SongReport};
+use crate::retry::{RetryEntry, RetryQueue, RETRY_QUEUE_PATH};
+use crate::types::{Comment, CommentOutput, ResourceType, SongData, SongInfoOutput};
+
+// 每首歌最多读取的评论数
+const MAX_SONG_COMMENTS: usize = 10000;
+// 等待写入线程保存的评论区数
+const WRITE_QUEUE: usize = 50;
+// 预先检查的热门评论数
+const HOT_CHECK_LIMIT: i32 = 100;
+
+// 单首歌曲的扫描结果
+#[derive(Debug)]
+pub struct SongScan {
+    // 目标用户发表的评论，多个目标用户时混在一起，按 用户.用户ID 区分
+    pub matched: Vec<CommentOutput>,
+    // 已读取的评论总数
+    pub scanned: usize,
+    // 成功读取的页数
+    pub pages: usize,
+    // 发出的请求数，包括重试
+    pub requests: usize,
+    // 中途失败时的错误，之前匹配到的评论仍保留在 matched 中
+    pub error: Option<anyhow::Error>,
+    // 失败页的分页位置，重试时从这里继续
+    pub failed_at: Option<PageCursor>,
+}
+
+// 批量抓取的选项
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    // 进度输出方式
+    pub progress: ProgressMode,
+    // 同时显示的单曲进度条上限
+    pub max_visible_bars: usize,
+    // 额外的筛选条件，评论需同时满足；导出时只写入满足条件的评论
+    pub filter: Option<Filter>,
+    // 按用户抓取时同时查找其他人 @ 或回复目标用户的评论
+    pub mentions: bool,
+    // 按用户抓取时先检查热门评论，所有目标用户都在其中时不再翻页。
+    // 更省请求，但会漏掉目标用户不在热门评论中的其他评论
+    pub hot_first: bool,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            progress: ProgressMode::Bars,
+            max_visible_bars: 10,
+            filter: None,
+            mentions: false,
+            hot_first: false,
+        }
+    }
+}
+
+// 扫描一首歌时怎样判断评论是否匹配
+#[derive(Debug, Clone, Default)]
+pub struct CommentMatcher {
+    // 要查找其本人评论的用户，已排序去重
+    pub target_uids: Vec<i64>,
+    // 额外的筛选条件，本人评论和提及都需满足
+    pub filter: Option<Filter>,
+    // 同时查找提及这些用户的评论
+    pub mentions: Vec<MentionTarget>,
+}
+
+impl CommentMatcher {
+    pub fn users(target_uids: &[i64]) -> Self {
+        let mut target_uids = target_uids.to_vec();
+        target_uids.sort();
+        target_uids.dedup();
+        Self {
+            target_uids,
+            ..Self::default()
+        }
+    }
+
+    // 评论对应的目标用户以及提及方式，为 None 时是该用户本人的评论。
+    // 一条评论可能同时提及多个目标用户
+    pub fn matches(&self, comment: &Comment) -> Vec<(i64, Option<MentionKind>)> {
+        if self.filter.as_ref().is_some_and(|f| !f.matches(comment)) {
+            return Vec::new();
+        }
+        let mut matches = Vec::new();
+        if self.target_uids.contains(&comment.user.user_id) {
+            matches.push((comment.user.user_id, None));
+        }
+        for target in &self.mentions {
+            if let Some(kind) = target.mention(comment) {
+                matches.push((target.uid, Some(kind)));
+            }
+        }
+        matches
+    }
+}
+
+// scan_song_with 匹配到的一条评论
+#[derive(Debug)]
+pub struct CommentMatch {
+    // 对应的目标用户
+    pub uid: i64,
+    // 为 None 时是该用户本人的评论
+    pub mention: Option<MentionKind>,
+    pub comment: CommentOutput,
+}
+
+// 各目标用户本人评论上出现过的昵称
+type SeenNicknames = BTreeMap<i64, BTreeSet<String>>;
+
+// 待抓取的一首歌或其他资源的评论区，start 不在开头时表示从上次失败处继续
+struct SongJob {
+    resource: ResourceType,
+    song_id: i64,
+    name: String,
+    // 歌曲信息，写在评论文件旁边；重试和非歌曲资源时为 None
+    info: Option<SongInfoOutput>,
+    matcher: CommentMatcher,
+    start: PageCursor,
+    // 之前已读取的评论数
+    scanned: usize,
+}
+
+// 基于 MusicApi 的评论抓取，所有实现了 MusicApi 的类型都可以使用
+#[async_trait]
+pub trait SongCrawler: MusicApi {
+    // 逐页扫描一首歌的评论，筛选出目标用户发表的评论
+    async fn scan_song_for_user(&self, song_id: i64, target_uid: i64, progress: &ProgressBar) -> SongScan {
+        self.scan_song_for_users(song_id, &[target_uid], progress).await
+    }
+
+    // 一次翻页同时筛选多个目标用户的评论，请求数与只找一个用户时相同
+    async fn scan_song_for_users(&self, song_id: i64, target_uids: &[i64], progress: &ProgressBar) -> SongScan {
+        self.resume_song_scan(song_id, target_uids, PageCursor::Offset(0), MAX_SONG_COMMENTS, progress)
+            .await
+    }
+
+    // 从 start 处开始扫描，最多读取 limit 条评论
+    async fn resume_song_scan(
+        &self,
+        song_id: i64,
+        target_uids: &[i64],
+        start: PageCursor,
+        limit: usize,
+        progress: &ProgressBar,
+    ) -> SongScan {
+        let mut matched = Vec::new();
+        let matcher = CommentMatcher::users(target_uids);
+        let mut scan = self
+            .scan_song_with(song_id, &matcher, start, limit, progress, &mut |m| {
+                matched.push(m.comment);
+                Ok(())
+            })
+            .await;
+        scan.matched = matched;
+        scan
+    }
+
+    // 与 resume_song_scan 相同，但按 matcher 匹配，匹配到的评论逐条交给 on_match，不在 SongScan.matched 中累积。
+    // on_match 出错时扫描在当前页停止，错误记在 SongScan.error 中
+    #[tracing::instrument(name = "song", skip(self, matcher, progress, on_match), fields(target_uids = ?matcher.target_uids))]
+    async fn scan_song_with(
+        &self,
+        song_id: i64,
+        matcher: &CommentMatcher,
+        start: PageCursor,
+        limit: usize,
+        progress: &ProgressBar,
+        on_match: &mut (dyn FnMut(CommentMatch) -> Result<()> + Send),
+    ) -> SongScan {
+        self.scan_comments_with(ResourceType::Song, song_id, matcher, start, limit, progress, on_match)
+            .await
+    }
+
+    // 与 scan_song_with 相同，但扫描任意资源的评论区
+    #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(
+        name = "comments",
+        skip(self, matcher, progress, on_match),
+        fields(
+            %resource,
+            target_uids = ?matcher.target_uids,
+            filter = matcher.filter.as_ref().map(tracing::field::display),
+            mentions = !matcher.mentions.is_empty(),
+        )
+    )]
+    async fn scan_comments_with(
+        &self,
+        resource: ResourceType,
+        id: i64,
+        matcher: &CommentMatcher,
+        start: PageCursor,
+        limit: usize,
+        progress: &ProgressBar,
+        on_match: &mut (dyn FnMut(CommentMatch) -> Result<()> + Send),
+    ) -> SongScan {
+        let mut scan = SongScan {
+            matched: Vec::new(),
+            scanned: 0,
+            pages: 0,
+            requests: 0,
+            error: None,
+            failed_at: None,
+        };
+
+        // 每页获取 100 条评论
+        let stats = Arc::new(StreamStats::default());
+        let options = CommentStreamOptions {
+            resource,
+            max_comments: Some(limit),
+            start,
+            stats: Some(stats.clone()),
+            ..CommentStreamOptions::default()
+        };
+        let mut comments = std::pin::pin!(self.comment_stream(id, options));
+        let mut sized = false;
+        let mut matched = 0;
+        while let Some(result) = comments.next().await {
+            match result {
+                Ok(comment) => {
+                    // 拿到第一页后按接口返回的总数设置进度条长度，续抓时扣除已跳过的部分
+                    if !sized {
+                        if let Some(total) = stats.total() {
+                            let skipped = match start {
+                                PageCursor::Offset(offset) => offset.max(0) as usize,
+                                PageCursor::Before(_) => 0,
+                            };
+                            progress.set_length(total.saturating_sub(skipped).min(limit) as u64);
+                        }
+                        sized = true;
+                    }
+                    scan.scanned += 1;
+                    progress.inc(1);
+                    let matches = matcher.matches(&comment);
+                    matched += matches.len();
+                    let saved = matches.into_iter().try_for_each(|(uid, mention)| {
+                        on_match(CommentMatch {
+                            uid,
+                            mention,
+                            comment: CommentOutput::from(comment.clone()),
+                        })
+                    });
+                    if let Err(e) = saved {
+                        scan.error = Some(e.context("保存评论失败"));
+                        scan.failed_at = stats.cursor();
+                        break;
+                    }
+                }
+                Err(e) => {
+                    scan.error = Some(e);
+                    scan.failed_at = stats.cursor();
+                    break;
+                }
+            }
+        }
+        scan.pages = stats.pages();
+        scan.requests = stats.requests();
+        tracing::info!(scanned = scan.scanned, matched, pages = scan.pages, "评论扫描完成");
+        scan
+    }
+
+    // 并发获取一组用户在歌曲下的评论，每首歌只翻页一次，评论按用户写入 comments/user_{uid}/。
+    // 缺少元数据的歌曲先通过 /song/detail 补全，歌曲信息写在每个评论文件旁边的 song_{id}.info.json。
+    // options.mentions 为 true 时提及目标用户的评论写入 comments/user_{uid}/mentions/。
+    // 结束后把汇总报告写入 comments/report.json，失败的歌曲记入 comments/retry_queue.json
+    async fn get_user_comments_for_songs(
+        &self,
+        songs: &[SongData],
+        target_uids: &[i64],
+        options: &CrawlOptions,
+    ) -> Result<CrawlReport> {
+        let mut songs: Vec<_> = songs.iter().map(|data| data.song.clone()).collect();
+        if let Err(e) = metadata::enrich_songs(self, &mut songs).await {
+            tracing::warn!(error = %e, "获取歌曲详情失败，歌曲信息只包含 ID 和歌名");
+        }
+        let targets = songs
+            .iter()
+            .map(|song| CrawlTarget {
+                resource: ResourceType::Song,
+                id: song.id,
+                name: song.name.clone(),
+                info: Some(SongInfoOutput::from(song)),
+            })
+            .collect();
+        crawl_for_users(self, targets, target_uids, options).await
+    }
+
+    // 与 get_user_comments_for_songs 相同，但在歌单、专辑、MV 等任意资源的评论区中查找，
+    // 评论写入 comments/user_{uid}/{类型}_{id}.json，例如 album_32311.json
+    async fn get_user_comments_for_resources(
+        &self,
+        resources: &[(ResourceType, i64)],
+        target_uids: &[i64],
+        options: &CrawlOptions,
+    ) -> Result<CrawlReport> {
+        let targets = resources
+            .iter()
+            .map(|&(resource, id)| CrawlTarget {
+                resource,
+                id,
+                name: id.to_string(),
+                info: None,
+            })
+            .collect();
+        crawl_for_users(self, targets, target_uids, options).await
+    }
+
+    // 只处理重试队列中的歌曲，各自从失败的位置继续，新匹配的评论合并进已有文件，
+    // 报告写入 comments/retry_report.json。筛选条件和是否查找提及沿用队列中记录的，不使用 options 中的设置
+    async fn retry_failed_songs(&self, queue_path: &Path, options: &CrawlOptions) -> Result<CrawlReport> {
+        let queue = RetryQueue::load(queue_path)?;
+        let mut mention_uids: Vec<i64> = queue
+            .entries
+            .iter()
+            .filter(|entry| entry.mentions)
+            .flat_map(|entry| entry.target_uids.iter().copied())
+            .collect();
+        mention_uids.sort();
+        mention_uids.dedup();
+        let mention_targets = mentions::resolve_mention_targets(self, &mention_uids).await?;
+        let jobs = queue
+            .entries
+            .into_iter()
+            .map(|entry| SongJob {
+                resource: entry.resource,
+                song_id: entry.song_id,
+                name: entry.name,
+                info: None,
+                matcher: CommentMatcher {
+                    mentions: mention_targets
+                        .iter()
+                        .filter(|target| entry.mentions && entry.target_uids.contains(&target.uid))
+                        .cloned()
+                        .collect(),
+                    target_uids: entry.target_uids,
+                    filter: entry.filter,
+                },
+                start: entry.cursor,
+                scanned: entry.scanned,
+            })
+            .collect();
+        let report = crawl_songs(self, jobs, queue_path, options).await?;
+        report.save(Path::new("comments/retry_report.json"))?;
+        Ok(report)
+    }
+}
+
+impl<T: MusicApi + ?Sized> SongCrawler for T {}
+
+// 要查找的一个评论区
+struct CrawlTarget {
+    resource: ResourceType,
+    id: i64,
+    name: String,
+    info: Option<SongInfoOutput>,
+}
+
+// 在一组评论区中查找目标用户，报告写入 comments/report.json
+async fn crawl_for_users<A: MusicApi + ?Sized>(
+    api: &A,
+    targets: Vec<CrawlTarget>,
+    target_uids: &[i64],
+    options: &CrawlOptions,
+) -> Result<CrawlReport> {
+    let mut matcher = CommentMatcher::users(target_uids);
+    matcher.filter = options.filter.clone();
+    if options.mentions {
+        matcher.mentions = mentions::resolve_mention_targets(api, &matcher.target_uids).await?;
+    }
+    let jobs = targets
+        .into_iter()
+        .map(|target| SongJob {
+            resource: target.resource,
+            song_id: target.id,
+            name: target.name,
+            info: target.info,
+            matcher: matcher.clone(),
+            start: PageCursor::Offset(0),
+            scanned: 0,
+        })
+        .collect();
+    let report = crawl_songs(api, jobs, Path::new(RETRY_QUEUE_PATH), options).await?;
+    report.save(Path::new("comments/report.json"))?;
+    Ok(report)
+}
+
+// 并发抓取一组歌曲，保存评论文件，并把失败的歌曲写入重试队列、成功的移出队列
+async fn crawl_songs<A: MusicApi + ?Sized>(
+    api: &A,
+    jobs: Vec<SongJob>,
+    queue_path: &Path,
+    options: &CrawlOptions,
+) -> Result<CrawlReport> {
+    // 创建 comments 目录用于保存评论文件
+    fs::create_dir_all("comments")?;
+    let started_at = chrono::Local::now().to_rfc3339();
+    let started = Instant::now();
+    let mut target_uids: Vec<i64> = jobs
+        .iter()
+        .flat_map(|job| job.matcher.target_uids.iter().copied())
+        .collect();
+    target_uids.sort();
+    target_uids.dedup();
+
+    // 设置并发数为 50
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(50));
+
+    let progress = CrawlProgress::new(jobs.len(), options.progress, options.max_visible_bars);
+    let writer = BoundedWriter::spawn(
+        WRITE_QUEUE,
+        (),
+        |_, write: SongWrite| {
+            write.save();
+            Ok(())
+        },
+        |_| Ok(()),
+    );
+
+    // 创建歌曲处理流
+    let jobs = &jobs;
+    let song_stream = stream::iter(0..jobs.len()).map({
+        let progress = progress.clone();
+        let sender = writer.sender();
+        move |song_index| {
+            let job = &jobs[song_index];
+            let semaphore = semaphore.clone();
+            let progress = progress.clone();
+            let sender = sender.clone();
+
+            async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                let song_progress = progress.song_bar(song_index);
+                progress.start_song(
+                    song_index,
+                    format!("{} {}/{} {}", job.resource.label(), song_index + 1, jobs.len(), job.name),
+                );
+                let limit = MAX_SONG_COMMENTS.saturating_sub(job.scanned);
+
+                // 匹配到的评论先按用户收集，扫描完后交给写入线程，续抓时与已有评论合并。
+                // 每个评论区最多读取 MAX_SONG_COMMENTS 条，收集的评论不会超过这个数
+                let resumed = job.start != PageCursor::Offset(0);
+                let mut comments: BTreeMap<i64, Vec<CommentOutput>> = BTreeMap::new();
+                let mut mention_comments: BTreeMap<i64, Vec<MentionOutput>> = BTreeMap::new();
+                let mut nicknames: SeenNicknames = BTreeMap::new();
+                let mut on_match = |m: CommentMatch| -> Result<()> {
+                    let uid = m.uid;
+                    match m.mention {
+                        None => {
+                            // 只在查找提及时记录昵称，不查找提及的抓取不动昵称记录
+                            if job.matcher.mentions.iter().any(|target| target.uid == uid) {
+                                nicknames.entry(uid).or_default().insert(m.comment.用户.昵称.clone());
+                            }
+                            comments.entry(uid).or_default().push(m.comment);
+                        }
+                        Some(kind) => mention_comments.entry(uid).or_default().push(MentionOutput::new(kind, m.comment)),
+                    }
+                    Ok(())
+                };
+
+                // 热门评论里已经找到所有目标用户时不再翻页
+                let hot = if options.hot_first && !resumed {
+                    match check_hot_comments(api, job.resource, job.song_id, &job.matcher, &mut on_match).await {
+                        Ok(hot) => Some(hot),
+                        Err(e) => {
+                            tracing::warn!(resource = %job.resource, id = job.song_id, error = %e, "检查热门评论失败，继续翻页");
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+                let hot_only = hot.as_ref().is_some_and(|hot| hot.complete);
+                let scan = match &hot {
+                    Some(hot) if hot.complete => {
+                        song_progress.set_length(hot.read as u64);
+                        song_progress.set_position(hot.read as u64);
+                        SongScan {
+                            matched: Vec::new(),
+                            scanned: hot.read,
+                            pages: 1,
+                            requests: 1,
+                            error: None,
+                            failed_at: None,
+                        }
+                    }
+                    _ => {
+                        let mut scan = api
+                            .scan_comments_with(
+                                job.resource,
+                                job.song_id,
+                                &job.matcher,
+                                job.start,
+                                limit,
+                                &song_progress,
+                                &mut on_match,
+                            )
+                            .await;
+                        if hot.is_some() {
+                            scan.requests += 1;
+                        }
+                        scan
+                    }
+                };
+                // 评论文件和歌曲信息由写入线程保存，worker 线程不做磁盘读写。
+                // 同一条评论可能先在热门评论中出现，按去重后写入文件的条数计数
+                let (done, saved) = oneshot::channel();
+                let write = SongWrite {
+                    resource: job.resource,
+                    id: job.song_id,
+                    merge: resumed,
+                    comments,
+                    mentions: mention_comments,
+                    info: job.info.clone(),
+                    done,
+                };
+                let saved = match sender.send(write).await {
+                    Ok(()) => saved.await.unwrap_or_else(|_| SongWritten::failed("写入线程已停止")),
+                    Err(e) => SongWritten::failed(format!("{:#}", e)),
+                };
+                let mut matched_by_user: BTreeMap<i64, usize> =
+                    job.matcher.target_uids.iter().map(|&uid| (uid, 0)).collect();
+                matched_by_user.extend(saved.comments);
+                let mut mentions_by_user: BTreeMap<i64, usize> =
+                    job.matcher.mentions.iter().map(|target| (target.uid, 0)).collect();
+                mentions_by_user.extend(saved.mentions);
+                if let Some(e) = &scan.error {
+                    tracing::error!(resource = %job.resource, id = job.song_id, error = %e, "获取评论失败");
+                }
+
+                // 只有保存失败时从这次的起点重新抓取
+                let save_failed = scan.error.is_none() && saved.error.is_some();
+                let error = match &scan.error {
+                    Some(e) => Some(format!("{:#}", e)),
+                    None => saved.error,
+                };
+                let retry = error.clone().map(|error| RetryEntry {
+                    resource: job.resource,
+                    song_id: job.song_id,
+                    name: job.name.clone(),
+                    target_uids: job.matcher.target_uids.clone(),
+                    filter: job.matcher.filter.clone(),
+                    mentions: !job.matcher.mentions.is_empty(),
+                    cursor: if save_failed { job.start } else { scan.failed_at.unwrap_or(job.start) },
+                    scanned: if save_failed { job.scanned } else { job.scanned + scan.scanned },
+                    error,
+                    // 本次失败，与队列中已有的次数在 upsert 时累加
+                    attempts: 1,
+                    failed_at: chrono::Local::now().to_rfc3339(),
+                });
+                let report = SongReport {
+                    resource: job.resource,
+                    song_id: job.song_id,
+                    name: job.name.clone(),
+                    target_uids: job.matcher.target_uids.clone(),
+                    scanned: scan.scanned,
+                    pages: scan.pages,
+                    requests: scan.requests,
+                    matched: matched_by_user.values().sum(),
+                    matched_by_user,
+                    mentions_by_user,
+                    hot_only,
+                    error,
+                };
+
+                progress.finish_song(song_index, &report);
+
+                (report, retry, nicknames)
+            }
+        }
+    });
+
+    // 并发处理所有歌曲，最多 50 个并发
+    let outcomes: Vec<(SongReport, Option<RetryEntry>, SeenNicknames)> = song_stream.buffer_unordered(50).collect().await;
+
+    progress.finish();
+    writer.finish().await?;
+
+    // 查找提及时本人评论上出现的昵称记入昵称记录，之后查找提及时使用。
+    // 没有查找提及的歌曲不会收集昵称，也就不会读写昵称记录
+    let mut nicknames: SeenNicknames = BTreeMap::new();
+    for (_, _, seen) in &outcomes {
+        for (&uid, names) in seen {
+            nicknames.entry(uid).or_default().extend(names.iter().cloned());
+        }
+    }
+    for (uid, names) in nicknames {
+        let mut target = MentionTarget::load(uid)?;
+        let mut changed = false;
+        for name in &names {
+            changed |= target.insert(name);
+        }
+        if changed {
+            target.save()?;
+        }
+    }
+
+    // 成功的移出队列，失败的累计失败次数
+    let mut queue = RetryQueue::load(queue_path)?;
+    let mut reports = Vec::with_capacity(outcomes.len());
+    for (report, retry, _) in outcomes {
+        match retry {
+            Some(entry) => queue.upsert(entry),
+            None => queue.remove(report.resource, report.song_id, &report.target_uids),
+        }
+        reports.push(report);
+    }
+    queue.save(queue_path)?;
+    if !queue.is_empty() {
+        tracing::warn!(songs = queue.entries.len(), path = %queue_path.display(), "部分歌曲抓取失败，已记入重试队列");
+    }
+    reports.sort_by_key(|r| {
+        jobs.iter()
+            .position(|j| j.resource == r.resource && j.song_id == r.song_id && j.matcher.target_uids == r.target_uids)
+    });
+
+    Ok(CrawlReport {
+        target_uids,
+        started_at,
+        elapsed_secs: started.elapsed().as_secs_f64(),
+        songs: reports,
+    })
+}
+
+// 热门评论检查的结果
+struct HotCheck {
+    // 读取的热门评论数
+    read: usize,
+    // 是否所有目标用户都已在热门评论中找到，查找提及时总是 false
+    complete: bool,
+}
+
+// 读取一个评论区的热门评论（一次请求），匹配到的评论交给 on_match
+async fn check_hot_comments<A: MusicApi + ?Sized>(
+    api: &A,
+    resource: ResourceType,
+    id: i64,
+    matcher: &CommentMatcher,
+    on_match: &mut (dyn FnMut(CommentMatch) -> Result<()> + Send),
+) -> Result<HotCheck> {
+    let response = api.get_hot_comments(resource, id, HOT_CHECK_LIMIT, 0).await?;
+    if response.code != 200 {
+        return Err(anyhow::anyhow!("获取热门评论失败，状态码 {}", response.code));
+    }
+    let mut found = HashSet::new();
+    let read = response.hot_comments.len();
+    for comment in response.hot_comments {
+        for (uid, mention) in matcher.matches(&comment) {
+            if mention.is_none() {
+                found.insert(uid);
+            }
+            on_match(CommentMatch {
+                uid,
+                mention,
+                comment: CommentOutput::from(comment.clone()),
+            })?;
+        }
+    }
+    let complete = matcher.mentions.is_empty()
+        && !matcher.target_uids.is_empty()
+        && matcher.target_uids.iter().all(|uid| found.contains(uid));
+    tracing::debug!(%resource, id, read, found = found.len(), complete, "热门评论检查完成");
+    Ok(HotCheck { read, complete })
+}
+
+// 写入评论文件的一条记录
+trait SavedComment: Serialize + DeserializeOwned {
+    fn comment_id(&self) -> i64;
+}
+
+impl SavedComment for CommentOutput {
+    fn comment_id(&self) -> i64 {
+        self.评论ID
+    }
+}
+
+impl SavedComment for MentionOutput {
+    fn comment_id(&self) -> i64 {
+        self.评论.评论ID
+    }
+}
+
+// 某个用户在一首歌下的评论文件，例如 comments/user_{uid}/song_{id}.json，边抓边写，
+// 全部写完后才替换原文件。merge 为 true 时先写入原有评论，再按评论 ID 去重追加
+struct SongCommentsFile<T> {
+    writer: JsonArrayWriter,
+    seen: HashSet<i64>,
+    _item: std::marker::PhantomData<T>,
+}
+
+impl<T: SavedComment> SongCommentsFile<T> {
+    fn open(path: PathBuf, merge: bool) -> Result<Self> {
+        let existing: Vec<T> = if merge && path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            Vec::new()
+        };
+        let mut file = Self {
+            writer: JsonArrayWriter::create(&path)?,
+            seen: HashSet::new(),
+            _item: std::marker::PhantomData,
+        };
+        for comment in existing {
+            file.push(comment)?;
+        }
+        Ok(file)
+    }
+
+    // 返回是否写入，已有的评论跳过
+    fn push(&mut self, comment: T) -> Result<bool> {
+        if !self.seen.insert(comment.comment_id()) {
+            return Ok(false);
+        }
+        self.writer.push(&comment)?;
+        Ok(true)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.writer.finish()
+    }
+}
+
+// 一个评论区要保存的评论和歌曲信息，由 crawl_songs 的写入线程处理
+struct SongWrite {
+    resource: ResourceType,
+    id: i64,
+    // 与已有评论文件合并
+    merge: bool,
+    comments: BTreeMap<i64, Vec<CommentOutput>>,
+    mentions: BTreeMap<i64, Vec<MentionOutput>>,
+    info: Option<SongInfoOutput>,
+    done: oneshot::Sender<SongWritten>,
+}
+
+// 写入线程回传的结果
+#[derive(Default)]
+struct SongWritten {
+    // 各用户去重后新写入的评论数
+    comments: BTreeMap<i64, usize>,
+    mentions: BTreeMap<i64, usize>,
+    // 第一个保存失败的文件的错误
+    error: Option<String>,
+}
+
+impl SongWritten {
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Self::default()
+        }
+    }
+
+    fn record(&mut self, uid: i64, resource: ResourceType, id: i64, result: Result<usize>, mention: bool) {
+        match result {
+            Ok(added) if mention => {
+                self.mentions.insert(uid, added);
+            }
+            Ok(added) => {
+                self.comments.insert(uid, added);
+            }
+            Err(e) => {
+                tracing::error!(%resource, id, uid, error = %e, "保存评论文件失败");
+                self.error.get_or_insert_with(|| format!("保存评论文件失败: {:#}", e));
+            }
+        }
+    }
+}
+
+impl SongWrite {
+    // 每个文件单独保存，某个文件失败时其他文件照常写入。歌曲信息写在每个评论文件旁边
+    fn save(self) {
+        let mut written = SongWritten::default();
+        let mut dirs = Vec::new();
+        for (uid, comments) in self.comments {
+            let dir = user_dir(uid);
+            let result = save_comments(&dir, self.resource, self.id, self.merge, comments);
+            written.record(uid, self.resource, self.id, result, false);
+            dirs.push(dir);
+        }
+        for (uid, mentions) in self.mentions {
+            let dir = mentions_dir(uid);
+            let result = save_comments(&dir, self.resource, self.id, self.merge, mentions);
+            written.record(uid, self.resource, self.id, result, true);
+            dirs.push(dir);
+        }
+        if let Some(info) = &self.info {
+            for dir in dirs {
+                if let Err(e) = save_song_info(&dir, info) {
+                    tracing::error!(song_id = self.id, dir = %dir.display(), error = %e, "保存歌曲信息失败");
+                }
+            }
+        }
+        let _ = self.done.send(written);
+    }
+}
+
+// 把一个评论区的评论写入 dir 下的评论文件，返回去重后新写入的条数
+fn save_comments<T: SavedComment>(dir: &Path, resource: ResourceType, id: i64, merge: bool, comments: Vec<T>) -> Result<usize> {
+    let mut file = SongCommentsFile::open(dir.join(comments_file_name(resource, id)), merge)?;
+    let mut added = 0;
+    for comment in comments {
+        if file.push(comment)? {
+            added += 1;
+        }
+    }
+    file.finish()?;
+    Ok(added)
+}
+
+// 评论文件名，例如 song_186016.json、album_32311.json
+pub fn comments_file_name(resource: ResourceType, id: i64) -> String {
+    format!("{}_{}.json", resource.as_str(), id)
+}
+
+// 把歌曲信息写到 dir 下对应评论文件的旁边，例如 song_{id}.info.json
+fn save_song_info(dir: &Path, info: &SongInfoOutput) -> Result<()> {
+    let path = dir.join(format!("song_{}.info.json", info.歌曲ID));
+    output::write_atomic(&path, serde_json::to_string_pretty(info)?)
+}
+
+// 某个目标用户的评论目录
+pub fn user_dir(uid: i64) -> PathBuf {
+    Path::new("comments").join(format!("user_{}", uid))
+}
+
+// 提及某个目标用户的评论目录
+pub fn mentions_dir(uid: i64) -> PathBuf {
+    user_dir(uid).join("mentions")
+}
@@ -0,0 +1,137 @@
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyInit, KeyIvInit};
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use num_bigint::BigUint;
+use rand::Rng;
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes128EcbEnc = ecb::Encryptor<aes::Aes128>;
+type Aes128EcbDec = ecb::Decryptor<aes::Aes128>;
+
+// weapi 第一轮 AES 使用的固定密钥和两轮共用的 IV
+const PRESET_KEY: &[u8; 16] = b"0CoJUm6Qyw8W8jud";
+const IV: &[u8; 16] = b"0102030405060708";
+// 网页端内置的 RSA 公钥
+const RSA_MODULUS: &str = "00e0b509f6259df8642dbc35662901477df22677ec152b5ff68ace615bb7b725152b3ab17a876aea8a5aa76d2e417629ec4ee341f56135fccf695280104e0312ecbda92557c93870114af6c9d05c4f7f0c3685b7a46bee255932575cce10b424d813cfe4875d3e82047b97ddef52741d546b8e289dc6935b3ece0462db0a22b8e7";
+const RSA_EXPONENT: u32 = 0x010001;
+const BASE62: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+const EAPI_KEY: &[u8; 16] = b"e82ckenh8dichen8";
+const EAPI_SEPARATOR: &str = "-36cd479b6b5-";
+
+// weapi 请求的表单参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeapiForm {
+    pub params: String,
+    pub enc_sec_key: String,
+}
+
+impl WeapiForm {
+    pub fn into_pairs(self) -> Vec<(String, String)> {
+        vec![
+            ("params".to_string(), self.params),
+            ("encSecKey".to_string(), self.enc_sec_key),
+        ]
+    }
+}
+
+// 网页端接口加密：用随机的 16 位密钥对 JSON 做两轮 AES-CBC，再用 RSA 加密该密钥
+pub fn weapi(text: &str) -> WeapiForm {
+    let mut rng = rand::thread_rng();
+    let secret_key: [u8; 16] = std::array::from_fn(|_| BASE62[rng.gen_range(0..BASE62.len())]);
+    weapi_with_key(text, &secret_key)
+}
+
+// 使用指定密钥的 weapi 加密，结果是确定的，便于测试
+pub fn weapi_with_key(text: &str, secret_key: &[u8; 16]) -> WeapiForm {
+    let first = BASE64_STANDARD.encode(aes_cbc(text.as_bytes(), PRESET_KEY));
+    let params = BASE64_STANDARD.encode(aes_cbc(first.as_bytes(), secret_key));
+    WeapiForm {
+        params,
+        enc_sec_key: rsa_no_padding(secret_key),
+    }
+}
+
+fn aes_cbc(data: &[u8], key: &[u8; 16]) -> Vec<u8> {
+    Aes128CbcEnc::new(key.into(), IV.into()).encrypt_padded_vec_mut::<Pkcs7>(data)
+}
+
+// 网页端使用无填充的 RSA，密钥按字节倒序后加密，结果为 256 位十六进制
+fn rsa_no_padding(secret_key: &[u8; 16]) -> String {
+    let mut reversed = *secret_key;
+    reversed.reverse();
+    let modulus = BigUint::parse_bytes(RSA_MODULUS.as_bytes(), 16).expect("RSA 模数无效");
+    let encrypted = BigUint::from_bytes_be(&reversed).modpow(&BigUint::from(RSA_EXPONENT), &modulus);
+    format!("{:0>256}", encrypted.to_str_radix(16))
+}
+
+// 客户端接口加密：url 为 /api/ 开头的接口路径，返回大写十六进制的 params
+pub fn eapi(url: &str, text: &str) -> String {
+    let digest = md5_hex(format!("nobody{}use{}md5forencrypt", url, text).as_bytes());
+    let data = format!("{url}{sep}{text}{sep}{digest}", sep = EAPI_SEPARATOR);
+    Aes128EcbEnc::new(EAPI_KEY.into())
+        .encrypt_padded_vec_mut::<Pkcs7>(data.as_bytes())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+// 解密 eapi 加密的数据，用于加密的响应体和调试
+pub fn eapi_decrypt(data: &[u8]) -> Result<Vec<u8>> {
+    Aes128EcbDec::new(EAPI_KEY.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(data)
+        .map_err(|_| anyhow::anyhow!("eapi 数据解密失败"))
+}
+
+pub fn md5_hex(data: &[u8]) -> String {
+    Md5::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"{"id":"186016","limit":"20"}"#;
+
+    #[test]
+    fn weapi_matches_reference_implementation() {
+        let form = weapi_with_key(TEXT, b"abcdefghijklmnop");
+        assert_eq!(form.params, "P1Iom4bk6BUJc1KetSuDoEypUhhqEwlSGO6JkSqqqd1YkbtVnvTPLIyIYeKv/aQw");
+        assert_eq!(
+            form.enc_sec_key,
+            "d15a1683c992095d0c234c19966605c5c5964911268bbeda8cb8d08d834913e59d53b32358903a121b5fca784c1f5ae44951fd02524df58ecc98e52cc7cf8689b42c2e93ddf05b0592512d87f5960467e2f086c018849d76014d323500e30f13ef4cafbb0cf5a66731a3f1776c75ca35d0062dac70a3e33245afabcf47938487"
+        );
+    }
+
+    #[test]
+    fn weapi_uses_a_random_base62_key() {
+        let a = weapi(TEXT);
+        let b = weapi(TEXT);
+        assert_ne!(a.enc_sec_key, b.enc_sec_key);
+        assert_eq!(a.enc_sec_key.len(), 256);
+    }
+
+    #[test]
+    fn eapi_matches_reference_implementation_and_round_trips() {
+        let params = eapi("/api/song/detail", TEXT);
+        assert_eq!(
+            params,
+            "7D398AA5036D61F11B22021C618C242421D51F26B6A0246E121BFC7B69A3481FF88B4AC443F70EE0FEB08195D4310CFED2195FE301E79CC83AAFA536A4F66ABC12E514F1270A0B84390D813CA809455F41A77C43F41C2FB6F08673042A3D7089DDF39B8737754AC0C1E384CF55CA6BD8"
+        );
+
+        let bytes: Vec<u8> = (0..params.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&params[i..i + 2], 16).unwrap())
+            .collect();
+        let plain = String::from_utf8(eapi_decrypt(&bytes).unwrap()).unwrap();
+        assert!(plain.starts_with(&format!("/api/song/detail{}{}", EAPI_SEPARATOR, TEXT)));
+    }
+
+    #[test]
+    fn md5_hex_matches_known_digest() {
+        assert_eq!(md5_hex(b"password"), "5f4dcc3b5aa765d61d8327deb882cf99");
+    }
+}
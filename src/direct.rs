@@ -0,0 +1,491 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+
+use crate::crypto;
+use crate::types::ResourceType;
+use crate::transport::{build_client, read_response, HttpConfig, HttpRequest, HttpResponse, Transport};
+
+// 网易云音乐官方接口地址
+pub const DIRECT_BASE_URL: &str = "https://music.163.com";
+
+// 未配置 User-Agent 时使用的浏览器 UA，官方接口会拒绝不像浏览器的请求
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+// eapi 请求中模拟的客户端信息
+const EAPI_APP_VERSION: &str = "8.9.70";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crypto {
+    Weapi,
+    Eapi,
+}
+
+// 翻译后的官方接口请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectRequest {
+    pub crypto: Crypto,
+    // 以 /api/ 开头的接口路径，发送时按加密方式替换为 /weapi/ 或 /eapi/
+    pub api: String,
+    pub data: Map<String, Value>,
+}
+
+impl DirectRequest {
+    fn new(crypto: Crypto, api: impl Into<String>, data: Value) -> Self {
+        let Value::Object(data) = data else {
+            unreachable!("接口参数必须是 JSON 对象")
+        };
+        Self {
+            crypto,
+            api: api.into(),
+            data,
+        }
+    }
+
+    pub fn url_path(&self) -> String {
+        let prefix = match self.crypto {
+            Crypto::Weapi => "/weapi/",
+            Crypto::Eapi => "/eapi/",
+        };
+        self.api.replacen("/api/", prefix, 1)
+    }
+}
+
+// 把 NeteaseCloudMusicApi 风格的请求翻译为官方接口请求，参数与该项目保持一致
+pub fn translate(request: &HttpRequest) -> Result<DirectRequest> {
+    let param = |name: &str| {
+        request
+            .query_value(name)
+            .with_context(|| format!("{} 缺少参数 {}", request.path, name))
+    };
+    let param_or = |name: &str, default: &str| request.query_value(name).unwrap_or(default).to_string();
+
+    let direct = match request.path.as_str() {
+        "/login/cellphone" => {
+            let password = match request.query_value("md5_password") {
+                Some(md5) => md5.to_string(),
+                None => crypto::md5_hex(param("password")?.as_bytes()),
+            };
+            DirectRequest::new(
+                Crypto::Weapi,
+                "/api/login/cellphone",
+                json!({
+                    "phone": param("phone")?,
+                    "countrycode": param_or("countrycode", "86"),
+                    "password": password,
+                    "rememberLogin": "true",
+                }),
+            )
+        }
+        "/login/qr/key" => DirectRequest::new(Crypto::Weapi, "/api/login/qrcode/unikey", json!({ "type": 1 })),
+        "/login/qr/check" => DirectRequest::new(
+            Crypto::Weapi,
+            "/api/login/qrcode/client/login",
+            json!({ "key": param("key")?, "type": 1 }),
+        ),
+        "/user/detail" => DirectRequest::new(Crypto::Weapi, format!("/api/v1/user/detail/{}", param("uid")?), json!({})),
+        "/user/record" => DirectRequest::new(
+            Crypto::Weapi,
+            "/api/v1/play/record",
+            json!({ "uid": param("uid")?, "type": param_or("type", "0") }),
+        ),
+        "/user/playlist" => DirectRequest::new(
+            Crypto::Weapi,
+            "/api/user/playlist",
+            json!({
+                "uid": param("uid")?,
+                "limit": param_or("limit", "30"),
+                "offset": param_or("offset", "0"),
+                "includeVideo": true,
+            }),
+        ),
+        "/user/follows" => DirectRequest::new(
+            Crypto::Weapi,
+            format!("/api/user/getfollows/{}", param("uid")?),
+            json!({
+                "offset": param_or("offset", "0"),
+                "limit": param_or("limit", "30"),
+                "order": true,
+            }),
+        ),
+        "/user/followeds" => DirectRequest::new(
+            Crypto::Eapi,
+            format!("/api/user/getfolloweds/{}", param("uid")?),
+            json!({
+                "userId": param("uid")?,
+                "time": param_or("lasttime", "0"),
+                "limit": param_or("limit", "30"),
+                "offset": param_or("offset", "0"),
+                "getcounts": "true",
+            }),
+        ),
+        // 第一步先取歌单详情，DirectTransport 再按 trackIds 分页请求 /song/detail，见 playlist_page_ids
+        "/playlist/track/all" => DirectRequest::new(
+            Crypto::Weapi,
+            "/api/v6/playlist/detail",
+            json!({ "id": param("id")?, "n": 100000, "s": 8 }),
+        ),
+        "/song/detail" => {
+            let ids: Vec<&str> = param("ids")?.split(',').map(str::trim).filter(|id| !id.is_empty()).collect();
+            let c: Vec<String> = ids.iter().map(|id| format!(r#"{{"id":{}}}"#, id)).collect();
+            DirectRequest::new(
+                Crypto::Weapi,
+                "/api/v3/song/detail",
+                json!({ "c": format!("[{}]", c.join(",")) }),
+            )
+        }
+        "/follow" => {
+            let action = if param("t")? == "1" { "follow" } else { "delfollow" };
+            DirectRequest::new(Crypto::Weapi, format!("/api/user/{}/{}", action, param("id")?), json!({}))
+        }
+        // 各类资源的评论结构相同，只是 threadId 前缀不同；动态直接传入 threadId
+        "/comment/music" | "/comment/playlist" | "/comment/album" | "/comment/mv" | "/comment/video"
+        | "/comment/dj" | "/comment/event" => {
+            let (thread, rid) = match request.path.as_str() {
+                "/comment/event" => {
+                    let thread = param("threadId")?;
+                    let rid = thread.trim_start_matches(ResourceType::Event { owner: 0 }.thread_prefix());
+                    (thread.to_string(), rid.to_string())
+                }
+                path => {
+                    let resource = comment_resource(path);
+                    let id = param("id")?;
+                    (format!("{}{}", resource.thread_prefix(), id), id.to_string())
+                }
+            };
+            DirectRequest::new(
+                Crypto::Weapi,
+                format!("/api/v1/resource/comments/{}", thread),
+                json!({
+                    "rid": rid,
+                    "limit": param_or("limit", "20"),
+                    "offset": param_or("offset", "0"),
+                    "beforeTime": param_or("before", "0"),
+                }),
+            )
+        }
+        "/comment/hot" => {
+            let id = param("id")?;
+            let code = param_or("type", "0");
+            let prefix = code
+                .parse()
+                .ok()
+                .and_then(ResourceType::thread_prefix_of)
+                .with_context(|| format!("/comment/hot 不支持的资源类型 {}", code))?;
+            DirectRequest::new(
+                Crypto::Weapi,
+                format!("/api/v1/resource/hotcomments/{}{}", prefix, id),
+                json!({
+                    "rid": id,
+                    "limit": param_or("limit", "20"),
+                    "offset": param_or("offset", "0"),
+                    "beforeTime": 0,
+                }),
+            )
+        }
+        other => return Err(anyhow::anyhow!("直连模式不支持接口 {}", other)),
+    };
+    Ok(direct)
+}
+
+// 把官方接口的响应整理成 NeteaseCloudMusicApi 的格式
+pub fn adapt_response(request: &HttpRequest, mut body: Value, set_cookies: &[String]) -> Value {
+    match request.path.as_str() {
+        "/login/qr/key" => {
+            let code = body.get("code").cloned().unwrap_or(Value::Null);
+            json!({ "code": code, "data": body })
+        }
+        "/login/qr/check" => {
+            if body.get("code").and_then(Value::as_i64) == Some(803) && body.get("cookie").is_none() {
+                body["cookie"] = Value::String(cookie_pairs(set_cookies));
+            }
+            body
+        }
+        _ => body,
+    }
+}
+
+// /playlist/track/all 本页的歌曲 ID。歌单详情的 tracks 只包含前若干首（未登录时更少），
+// 完整的列表在 trackIds 中，与 NeteaseCloudMusicApi 的 playlist_track_all 一样按 offset/limit 切片
+fn playlist_page_ids(request: &HttpRequest, detail: &Value) -> Vec<i64> {
+    let number = |name: &str, default: usize| {
+        request.query_value(name).and_then(|v| v.parse().ok()).unwrap_or(default)
+    };
+    let (offset, limit) = (number("offset", 0), number("limit", usize::MAX));
+    match detail.pointer("/playlist/trackIds") {
+        Some(Value::Array(ids)) => ids
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .filter_map(|track| track.get("id").and_then(Value::as_i64))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// 评论接口对应的资源类型，动态的评论接口直接使用 threadId，不经过这里
+fn comment_resource(path: &str) -> ResourceType {
+    [
+        ResourceType::Song,
+        ResourceType::Playlist,
+        ResourceType::Album,
+        ResourceType::Mv,
+        ResourceType::Video,
+        ResourceType::Dj,
+    ]
+    .into_iter()
+    .find(|resource| resource.comment_path() == path)
+    .unwrap_or_default()
+}
+
+// 二维码内容不需要请求接口，直接由 key 拼出
+fn qr_create_response(request: &HttpRequest) -> Result<Value> {
+    let key = request.query_value("key").context("/login/qr/create 缺少参数 key")?;
+    Ok(json!({
+        "code": 200,
+        "data": { "qrurl": format!("https://music.163.com/login?codekey={}", key), "qrimg": null },
+    }))
+}
+
+// set-cookie 头只保留 名称=值 部分，便于之后直接作为 Cookie 头发送
+fn cookie_pairs(set_cookies: &[String]) -> String {
+    set_cookies
+        .iter()
+        .filter_map(|c| c.split(';').next())
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn cookie_value<'a>(cookie: Option<&'a str>, name: &str) -> Option<&'a str> {
+    cookie?
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| v)
+}
+
+// 不经过 NeteaseCloudMusicApi，自行加密后直接请求官方接口的传输层。
+// 接收的请求与 ReqwestTransport 相同，NeteaseMusicClient 无需改动
+pub struct DirectTransport {
+    client: reqwest::Client,
+    base_url: String,
+    real_ip: Option<String>,
+}
+
+impl DirectTransport {
+    pub fn new() -> Result<Self> {
+        Self::with_config(&HttpConfig {
+            base_url: DIRECT_BASE_URL.to_string(),
+            ..HttpConfig::default()
+        })
+    }
+
+    pub fn with_config(config: &HttpConfig) -> Result<Self> {
+        let config = HttpConfig {
+            user_agent: Some(config.user_agent.clone().unwrap_or_else(|| DEFAULT_USER_AGENT.to_string())),
+            ..config.clone()
+        };
+        Ok(Self {
+            client: build_client(&config)?,
+            base_url: config.base_url,
+            real_ip: config.real_ip,
+        })
+    }
+}
+
+// 按接口的加密方式生成表单和 Cookie 头
+fn encrypt(direct: &mut DirectRequest, cookie: Option<&str>) -> (Vec<(String, String)>, String) {
+    let csrf = cookie_value(cookie, "__csrf").unwrap_or_default().to_string();
+    match direct.crypto {
+        Crypto::Weapi => {
+            direct.data.insert("csrf_token".to_string(), Value::String(csrf));
+            let form = crypto::weapi(&Value::Object(direct.data.clone()).to_string());
+            (form.into_pairs(), cookie.unwrap_or_default().to_string())
+        }
+        Crypto::Eapi => {
+            let now = chrono::Local::now();
+            let mut header = json!({
+                "osver": "",
+                "deviceId": "",
+                "appver": EAPI_APP_VERSION,
+                "versioncode": "140",
+                "mobilename": "",
+                "buildver": now.timestamp().to_string(),
+                "resolution": "1920x1080",
+                "__csrf": csrf,
+                "os": "android",
+                "channel": "",
+                "requestId": format!("{}_{:04}", now.timestamp_millis(), now.timestamp_subsec_micros() % 10000),
+            });
+            if let Some(music_u) = cookie_value(cookie, "MUSIC_U") {
+                header["MUSIC_U"] = Value::String(music_u.to_string());
+            }
+            let cookie_header = header
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| format!("{}={}", k, v.as_str().unwrap_or_default()))
+                .collect::<Vec<_>>()
+                .join("; ");
+            direct.data.insert("header".to_string(), header);
+            let params = crypto::eapi(&direct.api, &Value::Object(direct.data.clone()).to_string());
+            (vec![("params".to_string(), params)], cookie_header)
+        }
+    }
+}
+
+impl DirectTransport {
+    // 翻译、加密并发送一个请求，响应整理成 NeteaseCloudMusicApi 的格式
+    async fn post(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let mut direct = translate(request)?;
+        let (form, cookie) = encrypt(&mut direct, request.cookie.as_deref());
+        let url = format!("{}{}", self.base_url, direct.url_path());
+        let mut builder = self
+            .client
+            .post(&url)
+            .header("Referer", DIRECT_BASE_URL)
+            .form(&form);
+        if !cookie.is_empty() {
+            builder = builder.header("Cookie", cookie);
+        }
+        if let Some(ip) = &self.real_ip {
+            builder = builder.header("X-Real-IP", ip).header("X-Forwarded-For", ip);
+        }
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("请求 {} 失败", url))?;
+
+        let mut response = read_response(response).await?;
+        if let Ok(body) = serde_json::from_slice::<Value>(&response.body) {
+            let body = adapt_response(request, body, &response.set_cookies);
+            response.body = body.to_string().into_bytes();
+        }
+        Ok(response)
+    }
+
+    // 先取歌单详情，再用 /song/detail 获取 trackIds 中本页的歌曲，响应即为 { code, songs }
+    async fn playlist_track_all(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let detail = self.post(request).await?;
+        let body = match serde_json::from_slice::<Value>(&detail.body) {
+            Ok(body) if body.get("code").and_then(Value::as_i64) == Some(200) => body,
+            _ => return Ok(detail),
+        };
+        let ids = playlist_page_ids(request, &body);
+        if ids.is_empty() {
+            return Ok(HttpResponse::json(&json!({ "code": 200, "songs": [] })));
+        }
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        let songs = HttpRequest {
+            path: "/song/detail".to_string(),
+            query: vec![("ids".to_string(), ids.join(","))],
+            cookie: request.cookie.clone(),
+        };
+        self.post(&songs).await
+    }
+}
+
+#[async_trait]
+impl Transport for DirectTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        match request.path.as_str() {
+            "/login/qr/create" => Ok(HttpResponse::json(&qr_create_response(&request)?)),
+            "/playlist/track/all" => self.playlist_track_all(&request).await,
+            _ => self.post(&request).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str, query: &[(&str, &str)]) -> HttpRequest {
+        HttpRequest {
+            path: path.to_string(),
+            query: query.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            cookie: None,
+        }
+    }
+
+    #[test]
+    fn translates_comment_requests() {
+        let direct = translate(&request(
+            "/comment/music",
+            &[("id", "186016"), ("limit", "100"), ("offset", "200")],
+        ))
+        .unwrap();
+        assert_eq!(direct.crypto, Crypto::Weapi);
+        assert_eq!(direct.url_path(), "/weapi/v1/resource/comments/R_SO_4_186016");
+        assert_eq!(direct.data["rid"], "186016");
+        assert_eq!(direct.data["offset"], "200");
+        assert_eq!(direct.data["beforeTime"], "0");
+
+        let hot = translate(&request("/comment/hot", &[("id", "32311"), ("type", "3"), ("limit", "50")])).unwrap();
+        assert_eq!(hot.url_path(), "/weapi/v1/resource/hotcomments/R_AL_3_32311");
+        assert_eq!(hot.data["limit"], "50");
+        assert!(translate(&request("/comment/hot", &[("id", "1"), ("type", "99")])).is_err());
+
+        let mv = translate(&request("/comment/mv", &[("id", "5436712")])).unwrap();
+        assert_eq!(mv.url_path(), "/weapi/v1/resource/comments/R_MV_5_5436712");
+        let event = translate(&request("/comment/event", &[("threadId", "A_EV_2_6559519868_32953014")])).unwrap();
+        assert_eq!(event.url_path(), "/weapi/v1/resource/comments/A_EV_2_6559519868_32953014");
+        assert_eq!(event.data["rid"], "6559519868_32953014");
+    }
+
+    #[test]
+    fn song_detail_lists_ids_in_c() {
+        let direct = translate(&request("/song/detail", &[("ids", "186016,186001")])).unwrap();
+        assert_eq!(direct.url_path(), "/weapi/v3/song/detail");
+        assert_eq!(direct.data["c"], r#"[{"id":186016},{"id":186001}]"#);
+    }
+
+    #[test]
+    fn followeds_use_eapi_and_passwords_are_hashed() {
+        let direct = translate(&request("/user/followeds", &[("uid", "1")])).unwrap();
+        assert_eq!(direct.url_path(), "/eapi/user/getfolloweds/1");
+
+        let login = translate(&request("/login/cellphone", &[("phone", "13800000000"), ("password", "password")])).unwrap();
+        assert_eq!(login.data["password"], "5f4dcc3b5aa765d61d8327deb882cf99");
+
+        assert!(translate(&request("/song/url", &[])).is_err());
+        assert!(translate(&request("/user/detail", &[])).is_err());
+    }
+
+    #[test]
+    fn adapts_qr_responses_to_proxy_api_format() {
+        let key = adapt_response(&request("/login/qr/key", &[]), json!({ "code": 200, "unikey": "abc" }), &[]);
+        assert_eq!(key["data"]["unikey"], "abc");
+
+        let check = adapt_response(
+            &request("/login/qr/check", &[]),
+            json!({ "code": 803, "message": "授权登陆成功" }),
+            &["MUSIC_U=token; Path=/".to_string(), "__csrf=csrf; Path=/".to_string()],
+        );
+        assert_eq!(check["cookie"], "MUSIC_U=token; __csrf=csrf");
+    }
+
+    #[test]
+    fn pages_playlist_tracks_from_track_ids() {
+        // tracks 只有前两首，trackIds 才是完整列表
+        let detail = json!({
+            "code": 200,
+            "playlist": {
+                "tracks": [{ "id": 1, "name": "a" }, { "id": 2, "name": "b" }],
+                "trackIds": [{ "id": 1 }, { "id": 2 }, { "id": 3 }, { "id": 4 }, { "id": 5 }],
+            },
+        });
+        let page = |offset: &str| {
+            playlist_page_ids(
+                &request("/playlist/track/all", &[("id", "9"), ("limit", "2"), ("offset", offset)]),
+                &detail,
+            )
+        };
+        assert_eq!(page("1"), vec![2, 3]);
+        assert_eq!(page("4"), vec![5]);
+        assert!(page("5").is_empty());
+        assert_eq!(playlist_page_ids(&request("/playlist/track/all", &[("id", "9")]), &detail), vec![1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::time::{sleep, Duration};
+
+use crate::api::MusicApi;
+use crate::comment_stream::{CommentStreamExt, CommentStreamOptions, StreamStats};
+use crate::crawler::CrawlOptions;
+use crate::output::{AtomicFile, BoundedWriter};
+use crate::progress::CrawlProgress;
+use crate::report::{CrawlReport, SongReport};
+use crate::types::{Comment, ResourceType, Song};
+use crate::REQUEST_DELAY_MS;
+
+// 抓取与写入之间最多缓存的评论数
+const WRITE_BUFFER: usize = 1000;
+
+// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum DumpFormat {
+    // 每行一条评论的 JSON
+    #[default]
+    Ndjson,
+    // SQLite 数据库的 comments 表
+    Sqlite,
+}
+
+impl DumpFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DumpFormat::Ndjson => "ndjson",
+            DumpFormat::Sqlite => "sqlite",
+        }
+    }
+}
+
+// 导出的一行评论
+#[derive(Debug, Serialize)]
+pub struct DumpRecord {
+    pub song_id: i64,
+    pub comment_id: i64,
+    pub user_id: i64,
+    pub nickname: String,
+    pub content: String,
+    pub time: i64,
+    pub liked_count: i32,
+}
+
+impl DumpRecord {
+    pub fn new(song_id: i64, comment: Comment) -> Self {
+        Self {
+            song_id,
+            comment_id: comment.comment_id,
+            user_id: comment.user.user_id,
+            nickname: comment.user.nickname,
+            content: comment.content,
+            time: comment.time,
+            liked_count: comment.liked_count,
+        }
+    }
+}
+
+// 评论的写入目标，评论读到一条写一条，不在内存中保留
+pub trait CommentSink: Send {
+    fn write(&mut self, record: &DumpRecord) -> Result<()>;
+
+    // 全部写完后调用，把数据落盘；没有调用就丢弃时不应留下不完整的输出
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+// 写入 .part 临时文件，finish 时改名为目标文件
+pub struct NdjsonSink {
+    file: AtomicFile,
+}
+
+impl NdjsonSink {
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            file: AtomicFile::create(path)?,
+        })
+    }
+}
+
+impl CommentSink for NdjsonSink {
+    fn write(&mut self, record: &DumpRecord) -> Result<()> {
+        serde_json::to_writer(&mut self.file, record)?;
+        self.file.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.file.commit()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::*;
+    use anyhow::Context;
+    use rusqlite::Connection;
+
+    // 每写入这么多行提交一次事务
+    const COMMIT_EVERY: usize = 1000;
+
+    // 写入 comments 表，同一首歌的同一条评论只保留一行，可以向已有数据库追加。
+    // 按事务分批提交，中断时数据库里只会缺少最后一批
+    pub struct SqliteSink {
+        conn: Connection,
+        pending: usize,
+    }
+
+    impl SqliteSink {
+        pub fn create(path: &Path) -> Result<Self> {
+            let conn = Connection::open(path).with_context(|| format!("无法打开数据库 {}", path.display()))?;
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS comments (
+                    song_id INTEGER NOT NULL,
+                    comment_id INTEGER NOT NULL,
+                    user_id INTEGER NOT NULL,
+                    nickname TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    time INTEGER NOT NULL,
+                    liked_count INTEGER NOT NULL,
+                    PRIMARY KEY (song_id, comment_id)
+                );
+                CREATE INDEX IF NOT EXISTS comments_user_id ON comments (user_id);",
+            )?;
+            Ok(Self { conn, pending: 0 })
+        }
+    }
+
+    impl CommentSink for SqliteSink {
+        fn write(&mut self, record: &DumpRecord) -> Result<()> {
+            if self.pending == 0 {
+                self.conn.execute_batch("BEGIN")?;
+            }
+            self.conn
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO comments
+                     (song_id, comment_id, user_id, nickname, content, time, liked_count)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?
+                .execute(rusqlite::params![
+                    record.song_id,
+                    record.comment_id,
+                    record.user_id,
+                    record.nickname,
+                    record.content,
+                    record.time,
+                    record.liked_count,
+                ])?;
+            self.pending += 1;
+            if self.pending >= COMMIT_EVERY {
+                self.conn.execute_batch("COMMIT")?;
+                self.pending = 0;
+            }
+            Ok(())
+        }
+
+        fn finish(self: Box<Self>) -> Result<()> {
+            if self.pending > 0 {
+                self.conn.execute_batch("COMMIT")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// 按格式创建写入目标
+pub fn open_sink(format: DumpFormat, path: &Path) -> Result<Box<dyn CommentSink>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match format {
+        DumpFormat::Ndjson => Ok(Box::new(NdjsonSink::create(path)?)),
+        #[cfg(feature = "sqlite")]
+        DumpFormat::Sqlite => Ok(Box::new(SqliteSink::create(path)?)),
+        #[cfg(not(feature = "sqlite"))]
+        DumpFormat::Sqlite => Err(anyhow::anyhow!("编译时未启用 sqlite 特性，无法导出 SQLite")),
+    }
+}
+
+// 读取歌单中的全部歌曲，翻页之间间隔 REQUEST_DELAY_MS
+pub async fn playlist_songs<A: MusicApi + ?Sized>(api: &A, playlist_id: i64) -> Result<Vec<Song>> {
+    const PAGE: i32 = 1000;
+    let mut songs = Vec::new();
+    loop {
+        if !songs.is_empty() {
+            sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+        }
+        let page = api
+            .get_playlist_tracks(playlist_id, Some(PAGE), Some(songs.len() as i32))
+            .await?;
+        if page.code != 200 {
+            return Err(anyhow::anyhow!("获取歌单 {} 失败，状态码 {}", playlist_id, page.code));
+        }
+        let count = page.songs.len();
+        songs.extend(page.songs);
+        if count < PAGE as usize {
+            return Ok(songs);
+        }
+    }
+}
+
+// 不筛选用户，把歌曲下的全部评论导出
+#[async_trait]
+pub trait CommentDumper: MusicApi {
+    // 并发翻页，评论经有界通道交给写入线程逐条写入 sink，每首歌最多读取 max_comments 条（None 为不限）。
+    // 设置了 options.filter 时只写入满足条件的评论
+    async fn dump_song_comments(
+        &self,
+        songs: &[Song],
+        sink: Box<dyn CommentSink>,
+        max_comments: Option<usize>,
+        options: &CrawlOptions,
+    ) -> Result<CrawlReport> {
+        let started_at = chrono::Local::now().to_rfc3339();
+        let started = Instant::now();
+        let writer = BoundedWriter::spawn(
+            WRITE_BUFFER,
+            sink,
+            |sink: &mut Box<dyn CommentSink>, record: DumpRecord| sink.write(&record),
+            |sink| sink.finish(),
+        );
+        let progress = CrawlProgress::new(songs.len(), options.progress, options.max_visible_bars);
+
+        let song_stream = stream::iter(0..songs.len()).map(|song_index| {
+            let song = &songs[song_index];
+            let progress = progress.clone();
+            let writer = writer.sender();
+            async move {
+                progress.start_song(song_index, format!("歌曲 {}/{} {}", song_index + 1, songs.len(), song.name));
+                let bar = progress.song_bar(song_index);
+                let stats = Arc::new(StreamStats::default());
+                let stream_options = CommentStreamOptions {
+                    max_comments,
+                    stats: Some(stats.clone()),
+                    ..CommentStreamOptions::default()
+                };
+                let mut scanned = 0;
+                let mut written = 0;
+                let mut error = None;
+                let mut comments = std::pin::pin!(self.comment_stream(song.id, stream_options));
+                while let Some(result) = comments.next().await {
+                    let comment = match result {
+                        Ok(comment) => comment,
+                        Err(e) => {
+                            error = Some(e);
+                            break;
+                        }
+                    };
+                    if bar.length().is_none() {
+                        if let Some(total) = stats.total() {
+                            bar.set_length(max_comments.map_or(total, |max| total.min(max)) as u64);
+                        }
+                    }
+                    scanned += 1;
+                    bar.inc(1);
+                    if options.filter.as_ref().is_some_and(|f| !f.matches(&comment)) {
+                        continue;
+                    }
+                    if let Err(e) = writer.send(DumpRecord::new(song.id, comment)).await {
+                        error = Some(e.context("写入评论失败"));
+                        break;
+                    }
+                    written += 1;
+                }
+                if let Some(e) = &error {
+                    tracing::error!(song_id = song.id, error = %e, "导出歌曲评论失败");
+                }
+                let report = SongReport {
+                    resource: ResourceType::Song,
+                    song_id: song.id,
+                    name: song.name.clone(),
+                    target_uids: Vec::new(),
+                    scanned,
+                    pages: stats.pages(),
+                    requests: stats.requests(),
+                    matched: written,
+                    matched_by_user: Default::default(),
+                    mentions_by_user: Default::default(),
+                    hot_only: false,
+                    error: error.map(|e| format!("{:#}", e)),
+                };
+                progress.finish_song(song_index, &report);
+                report
+            }
+        });
+
+        // 并发处理所有歌曲，最多 50 个并发
+        let mut reports: Vec<SongReport> = song_stream.buffer_unordered(50).collect().await;
+        progress.finish();
+        writer.finish().await?;
+        reports.sort_by_key(|r| songs.iter().position(|s| s.id == r.song_id));
+
+        Ok(CrawlReport {
+            target_uids: Vec::new(),
+            started_at,
+            elapsed_secs: started.elapsed().as_secs_f64(),
+            songs: reports,
+        })
+    }
+}
+
+impl<T: MusicApi + ?Sized> CommentDumper for T {}
@@ -0,0 +1,230 @@
+use std::fmt::Write;
+
+use crate::graph::{GraphEdge, GraphNode, SocialGraph};
+
+// 关系图导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum GraphFormat {
+    Graphml,
+    Gexf,
+    Dot,
+}
+
+impl GraphFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            GraphFormat::Graphml => "graphml",
+            GraphFormat::Gexf => "gexf",
+            GraphFormat::Dot => "dot",
+        }
+    }
+}
+
+impl SocialGraph {
+    // 双方互相关注时为 true
+    fn is_mutual(&self, edge: &GraphEdge) -> bool {
+        self.edges.contains(&GraphEdge {
+            source: edge.target,
+            target: edge.source,
+        })
+    }
+
+    pub fn export(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Graphml => self.to_graphml(),
+            GraphFormat::Gexf => self.to_gexf(),
+            GraphFormat::Dot => self.to_dot(),
+        }
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str("  <key id=\"nickname\" for=\"node\" attr.name=\"nickname\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"followeds\" for=\"node\" attr.name=\"followeds\" attr.type=\"int\"/>\n");
+        out.push_str("  <key id=\"vip_type\" for=\"node\" attr.name=\"vip_type\" attr.type=\"int\"/>\n");
+        out.push_str("  <key id=\"province\" for=\"node\" attr.name=\"province\" attr.type=\"int\"/>\n");
+        out.push_str("  <key id=\"mutual\" for=\"edge\" attr.name=\"mutual\" attr.type=\"boolean\"/>\n");
+        out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+        for node in self.nodes.values() {
+            let _ = writeln!(out, "    <node id=\"{}\">", node.user_id);
+            let _ = writeln!(out, "      <data key=\"nickname\">{}</data>", escape_xml(&node.nickname));
+            let _ = writeln!(out, "      <data key=\"followeds\">{}</data>", node.followeds);
+            let _ = writeln!(out, "      <data key=\"vip_type\">{}</data>", node.vip_type);
+            if let Some(province) = node.province {
+                let _ = writeln!(out, "      <data key=\"province\">{}</data>", province);
+            }
+            out.push_str("    </node>\n");
+        }
+        for (index, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+                index, edge.source, edge.target
+            );
+            let _ = writeln!(out, "      <data key=\"mutual\">{}</data>", self.is_mutual(edge));
+            out.push_str("    </edge>\n");
+        }
+        out.push_str("  </graph>\n");
+        out.push_str("</graphml>\n");
+        out
+    }
+
+    fn to_gexf(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        out.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+        out.push_str("    <attributes class=\"node\">\n");
+        out.push_str("      <attribute id=\"0\" title=\"followeds\" type=\"integer\"/>\n");
+        out.push_str("      <attribute id=\"1\" title=\"vip_type\" type=\"integer\"/>\n");
+        out.push_str("      <attribute id=\"2\" title=\"province\" type=\"integer\"/>\n");
+        out.push_str("    </attributes>\n");
+        out.push_str("    <attributes class=\"edge\">\n");
+        out.push_str("      <attribute id=\"0\" title=\"mutual\" type=\"boolean\"/>\n");
+        out.push_str("    </attributes>\n");
+        out.push_str("    <nodes>\n");
+        for node in self.nodes.values() {
+            let _ = writeln!(
+                out,
+                "      <node id=\"{}\" label=\"{}\">",
+                node.user_id,
+                escape_xml(&node.nickname)
+            );
+            out.push_str("        <attvalues>\n");
+            let _ = writeln!(out, "          <attvalue for=\"0\" value=\"{}\"/>", node.followeds);
+            let _ = writeln!(out, "          <attvalue for=\"1\" value=\"{}\"/>", node.vip_type);
+            if let Some(province) = node.province {
+                let _ = writeln!(out, "          <attvalue for=\"2\" value=\"{}\"/>", province);
+            }
+            out.push_str("        </attvalues>\n");
+            out.push_str("      </node>\n");
+        }
+        out.push_str("    </nodes>\n");
+        out.push_str("    <edges>\n");
+        for (index, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\">",
+                index, edge.source, edge.target
+            );
+            let _ = writeln!(
+                out,
+                "        <attvalues><attvalue for=\"0\" value=\"{}\"/></attvalues>",
+                self.is_mutual(edge)
+            );
+            out.push_str("      </edge>\n");
+        }
+        out.push_str("    </edges>\n");
+        out.push_str("  </graph>\n");
+        out.push_str("</gexf>\n");
+        out
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph netease {\n");
+        for node in self.nodes.values() {
+            let _ = writeln!(out, "  {} [{}];", node.user_id, dot_node_attrs(node));
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "  {} -> {} [mutual=\"{}\"];",
+                edge.source,
+                edge.target,
+                self.is_mutual(edge)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn dot_node_attrs(node: &GraphNode) -> String {
+    let mut attrs = format!(
+        "label=\"{}\", nickname=\"{}\", followeds=\"{}\", vip_type=\"{}\"",
+        escape_dot(&node.nickname),
+        escape_dot(&node.nickname),
+        node.followeds,
+        node.vip_type
+    );
+    if let Some(province) = node.province {
+        let _ = write!(attrs, ", province=\"{}\"", province);
+    }
+    attrs
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML 1.0 不允许出现的控制字符直接丢弃
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(user_id: i64, nickname: &str) -> GraphNode {
+        GraphNode {
+            user_id,
+            nickname: nickname.to_string(),
+            avatar_url: String::new(),
+            signature: None,
+            followeds: 10,
+            vip_type: 0,
+            province: Some(110000),
+            city: None,
+            depth: 0,
+            expanded: true,
+        }
+    }
+
+    fn sample_graph() -> SocialGraph {
+        let mut graph = SocialGraph::default();
+        graph.nodes.insert(1, node(1, "A & <B>"));
+        graph.nodes.insert(2, node(2, "say \"hi\""));
+        graph.nodes.insert(3, node(3, "C"));
+        graph.edges.insert(GraphEdge { source: 1, target: 2 });
+        graph.edges.insert(GraphEdge { source: 2, target: 1 });
+        graph.edges.insert(GraphEdge { source: 1, target: 3 });
+        graph
+    }
+
+    #[test]
+    fn dot_marks_mutual_edges() {
+        let dot = sample_graph().export(GraphFormat::Dot);
+        assert!(dot.contains("1 -> 2 [mutual=\"true\"]"));
+        assert!(dot.contains("2 -> 1 [mutual=\"true\"]"));
+        assert!(dot.contains("1 -> 3 [mutual=\"false\"]"));
+        assert!(dot.contains("label=\"say \\\"hi\\\"\""));
+    }
+
+    #[test]
+    fn xml_formats_escape_nicknames() {
+        let graph = sample_graph();
+        for format in [GraphFormat::Graphml, GraphFormat::Gexf] {
+            let xml = graph.export(format);
+            assert!(xml.contains("A &amp; &lt;B&gt;"));
+            assert!(!xml.contains("A & <B>"));
+            assert!(xml.contains("directed"));
+        }
+    }
+}
@@ -0,0 +1,618 @@
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::Comment;
+
+// 评论筛选条件。可以从表达式解析，例如
+//   liked>=100 && content~/晚安/
+//   (nickname~"猫" || user==12345) && time>="2024-01-01" && !content~"广告"
+// 也可以用 Filter::keyword、Filter::min_liked 等构造后用 and/or/! 组合。
+//
+// 字段：content 评论内容，nickname 昵称，liked 点赞数，time 发表时间，user（或 uid）用户 ID。
+// 文本字段支持 == 和 != 整体比较，~ 和 !~ 包含（"..."）或正则匹配（/.../）；
+// 数值字段支持 == != > >= < <=，time 的值可以是毫秒时间戳或本地时间 "YYYY-MM-DD [HH:MM[:SS]]"。
+// 单独的 "..." 或 /.../ 相当于 content~"..." 或 content~/.../
+#[derive(Debug, Clone)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Text(TextField, TextMatch),
+    Number(NumberField, Cmp, i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextField {
+    Content,
+    Nickname,
+}
+
+#[derive(Debug, Clone)]
+pub enum TextMatch {
+    Equals(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberField {
+    Liked,
+    // 毫秒时间戳
+    Time,
+    User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0, len: expr.chars().count() };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some((at, token)) => Err(syntax_error(*at, format!("多余的 {}", token))),
+        }
+    }
+
+    // 评论内容包含 keyword
+    pub fn keyword(keyword: impl Into<String>) -> Self {
+        Filter::Text(TextField::Content, TextMatch::Contains(keyword.into()))
+    }
+
+    // 评论内容匹配正则
+    pub fn content_regex(pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern).with_context(|| format!("无效的正则: {}", pattern))?;
+        Ok(Filter::Text(TextField::Content, TextMatch::Regex(regex)))
+    }
+
+    // 昵称包含 keyword
+    pub fn nickname(keyword: impl Into<String>) -> Self {
+        Filter::Text(TextField::Nickname, TextMatch::Contains(keyword.into()))
+    }
+
+    pub fn min_liked(count: i64) -> Self {
+        Filter::Number(NumberField::Liked, Cmp::Ge, count)
+    }
+
+    // 发表时间不早于 millis
+    pub fn since(millis: i64) -> Self {
+        Filter::Number(NumberField::Time, Cmp::Ge, millis)
+    }
+
+    // 发表时间早于 millis
+    pub fn until(millis: i64) -> Self {
+        Filter::Number(NumberField::Time, Cmp::Lt, millis)
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        Filter::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        Filter::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn matches(&self, comment: &Comment) -> bool {
+        match self {
+            Filter::And(a, b) => a.matches(comment) && b.matches(comment),
+            Filter::Or(a, b) => a.matches(comment) || b.matches(comment),
+            Filter::Not(inner) => !inner.matches(comment),
+            Filter::Text(field, text) => {
+                let value = match field {
+                    TextField::Content => &comment.content,
+                    TextField::Nickname => &comment.user.nickname,
+                };
+                match text {
+                    TextMatch::Equals(s) => value == s,
+                    TextMatch::Contains(s) => value.contains(s.as_str()),
+                    TextMatch::Regex(regex) => regex.is_match(value),
+                }
+            }
+            Filter::Number(field, cmp, expected) => {
+                let value = match field {
+                    NumberField::Liked => comment.liked_count as i64,
+                    NumberField::Time => comment.time,
+                    NumberField::User => comment.user.user_id,
+                };
+                match cmp {
+                    Cmp::Eq => value == *expected,
+                    Cmp::Ne => value != *expected,
+                    Cmp::Lt => value < *expected,
+                    Cmp::Le => value <= *expected,
+                    Cmp::Gt => value > *expected,
+                    Cmp::Ge => value >= *expected,
+                }
+            }
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Filter::parse(s)
+    }
+}
+
+// 输出为可以重新解析的表达式
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Or(a, b) => write!(f, "{} || {}", a, b),
+            Filter::And(a, b) => {
+                write_operand(f, a, |c| matches!(c, Filter::Or(..)))?;
+                f.write_str(" && ")?;
+                write_operand(f, b, |c| matches!(c, Filter::Or(..)))
+            }
+            Filter::Not(inner) => {
+                f.write_str("!")?;
+                write_operand(f, inner, |c| matches!(c, Filter::Or(..) | Filter::And(..)))
+            }
+            Filter::Text(field, text) => {
+                f.write_str(match field {
+                    TextField::Content => "content",
+                    TextField::Nickname => "nickname",
+                })?;
+                match text {
+                    TextMatch::Equals(s) => write!(f, "=={}", Quoted(s)),
+                    TextMatch::Contains(s) => write!(f, "~{}", Quoted(s)),
+                    TextMatch::Regex(regex) => {
+                        f.write_str("~/")?;
+                        let mut chars = regex.as_str().chars();
+                        while let Some(c) = chars.next() {
+                            match c {
+                                '\\' => {
+                                    f.write_str("\\")?;
+                                    if let Some(next) = chars.next() {
+                                        write!(f, "{}", next)?;
+                                    }
+                                }
+                                '/' => f.write_str("\\/")?,
+                                c => write!(f, "{}", c)?,
+                            }
+                        }
+                        f.write_str("/")
+                    }
+                }
+            }
+            Filter::Number(field, cmp, value) => {
+                let field = match field {
+                    NumberField::Liked => "liked",
+                    NumberField::Time => "time",
+                    NumberField::User => "user",
+                };
+                let cmp = match cmp {
+                    Cmp::Eq => "==",
+                    Cmp::Ne => "!=",
+                    Cmp::Lt => "<",
+                    Cmp::Le => "<=",
+                    Cmp::Gt => ">",
+                    Cmp::Ge => ">=",
+                };
+                write!(f, "{}{}{}", field, cmp, value)
+            }
+        }
+    }
+}
+
+fn write_operand(f: &mut fmt::Formatter<'_>, filter: &Filter, needs_parens: impl Fn(&Filter) -> bool) -> fmt::Result {
+    if needs_parens(filter) {
+        write!(f, "({})", filter)
+    } else {
+        write!(f, "{}", filter)
+    }
+}
+
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' | '\\' => write!(f, "\\{}", c)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+// 序列化为表达式字符串，便于写入重试队列
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Filter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let expr = String::deserialize(deserializer)?;
+        Filter::parse(&expr).map_err(|e| serde::de::Error::custom(format!("{:#}", e)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    Str(String),
+    Regex(String),
+    Op(&'static str),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Str(s) => write!(f, "{}", Quoted(s)),
+            Token::Regex(s) => write!(f, "/{}/", s),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::And => f.write_str("&&"),
+            Token::Or => f.write_str("||"),
+            Token::Not => f.write_str("!"),
+            Token::LParen => f.write_str("("),
+            Token::RParen => f.write_str(")"),
+        }
+    }
+}
+
+// at 为从 0 开始的字符位置，提示时从 1 开始计
+fn syntax_error(at: usize, message: impl fmt::Display) -> anyhow::Error {
+    anyhow::anyhow!("筛选表达式第 {} 个字符处：{}", at + 1, message)
+}
+
+fn tokenize(expr: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '&' if next == Some('&') => Token::And,
+            '|' if next == Some('|') => Token::Or,
+            '!' if next == Some('=') => Token::Op("!="),
+            '!' if next == Some('~') => Token::Op("!~"),
+            '!' => Token::Not,
+            '=' if next == Some('=') => Token::Op("=="),
+            '=' => Token::Op("="),
+            '>' if next == Some('=') => Token::Op(">="),
+            '>' => Token::Op(">"),
+            '<' if next == Some('=') => Token::Op("<="),
+            '<' => Token::Op("<"),
+            '~' => Token::Op("~"),
+            '"' | '\'' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error(start, "字符串没有结束")),
+                        Some(&q) if q == c => break,
+                        Some('\\') => {
+                            i += 1;
+                            value.push(*chars.get(i).ok_or_else(|| syntax_error(start, "字符串没有结束"))?);
+                        }
+                        Some(&ch) => value.push(ch),
+                    }
+                    i += 1;
+                }
+                Token::Str(value)
+            }
+            '/' => {
+                // 正则中的 \/ 表示斜杠，其余转义原样交给正则
+                let mut pattern = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax_error(start, "正则没有结束")),
+                        Some('/') => break,
+                        Some('\\') => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some('/') => pattern.push('/'),
+                                Some(&ch) => {
+                                    pattern.push('\\');
+                                    pattern.push(ch);
+                                }
+                                None => return Err(syntax_error(start, "正则没有结束")),
+                            }
+                        }
+                        Some(&ch) => pattern.push(ch),
+                    }
+                    i += 1;
+                }
+                Token::Regex(pattern)
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                i += 1;
+                while chars.get(i).is_some_and(|ch| ch.is_ascii_digit()) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text.parse().map_err(|_| syntax_error(start, format!("无效的数字 {}", text)))?;
+                tokens.push((start, Token::Number(value)));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                i += 1;
+                while chars.get(i).is_some_and(|ch| ch.is_alphanumeric() || *ch == '_') {
+                    i += 1;
+                }
+                tokens.push((start, Token::Ident(chars[start..i].iter().collect())));
+                continue;
+            }
+            c => return Err(syntax_error(start, format!("无法识别的字符 {}", c))),
+        };
+        i += match token {
+            Token::And | Token::Or => 2,
+            Token::Op(op) => op.len(),
+            _ => 1,
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+// 优先级从低到高：||、&&、!，同级从左到右结合
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // 表达式的字符数，用于在末尾报错
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<(usize, Token)> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| syntax_error(self.len, "表达式不完整"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek().is_some_and(|(_, t)| t == token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter> {
+        let mut filter = self.and()?;
+        while self.eat(&Token::Or) {
+            filter = filter.or(self.and()?);
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter> {
+        let mut filter = self.unary()?;
+        while self.eat(&Token::And) {
+            filter = filter.and(self.unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter> {
+        if self.eat(&Token::Not) {
+            return Ok(!self.unary()?);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Filter> {
+        let (at, token) = self.next()?;
+        match token {
+            Token::LParen => {
+                let filter = self.or()?;
+                match self.next()? {
+                    (_, Token::RParen) => Ok(filter),
+                    (at, token) => Err(syntax_error(at, format!("应为 )，实际为 {}", token))),
+                }
+            }
+            Token::Str(s) => Ok(Filter::keyword(s)),
+            Token::Regex(pattern) => regex_match(TextField::Content, at, &pattern),
+            Token::Ident(name) => self.comparison(at, &name),
+            token => Err(syntax_error(at, format!("应为条件，实际为 {}", token))),
+        }
+    }
+
+    fn comparison(&mut self, at: usize, name: &str) -> Result<Filter> {
+        let field = match name {
+            "content" => Field::Text(TextField::Content),
+            "nickname" => Field::Text(TextField::Nickname),
+            "liked" => Field::Number(NumberField::Liked),
+            "time" => Field::Number(NumberField::Time),
+            "user" | "uid" => Field::Number(NumberField::User),
+            _ => {
+                return Err(syntax_error(
+                    at,
+                    format!("未知字段 {}，可用字段为 content、nickname、liked、time、user", name),
+                ))
+            }
+        };
+        let (op_at, op) = match self.next()? {
+            (op_at, Token::Op(op)) => (op_at, op),
+            (at, token) => return Err(syntax_error(at, format!("{} 后应为比较符，实际为 {}", name, token))),
+        };
+        let (value_at, value) = self.next()?;
+        match field {
+            Field::Text(field) => {
+                let filter = match (op, value) {
+                    ("==" | "=" | "!=", Token::Str(s)) => Filter::Text(field, TextMatch::Equals(s)),
+                    ("~" | "!~", Token::Str(s)) => Filter::Text(field, TextMatch::Contains(s)),
+                    ("~" | "!~", Token::Regex(pattern)) => regex_match(field, value_at, &pattern)?,
+                    ("==" | "=" | "!=" | "~" | "!~", token) => {
+                        return Err(syntax_error(value_at, format!("{} 的值应为字符串或正则，实际为 {}", name, token)))
+                    }
+                    (op, _) => return Err(syntax_error(op_at, format!("{} 不支持比较符 {}", name, op))),
+                };
+                Ok(if op.starts_with('!') { !filter } else { filter })
+            }
+            Field::Number(field) => {
+                let cmp = match op {
+                    "==" | "=" => Cmp::Eq,
+                    "!=" => Cmp::Ne,
+                    "<" => Cmp::Lt,
+                    "<=" => Cmp::Le,
+                    ">" => Cmp::Gt,
+                    ">=" => Cmp::Ge,
+                    op => return Err(syntax_error(op_at, format!("{} 不支持比较符 {}", name, op))),
+                };
+                let value = match (field, value) {
+                    (_, Token::Number(n)) => n,
+                    (NumberField::Time, Token::Str(s)) => {
+                        parse_time(&s).map_err(|e| syntax_error(value_at, format!("{:#}", e)))?
+                    }
+                    (_, token) => return Err(syntax_error(value_at, format!("{} 的值应为数字，实际为 {}", name, token))),
+                };
+                Ok(Filter::Number(field, cmp, value))
+            }
+        }
+    }
+}
+
+enum Field {
+    Text(TextField),
+    Number(NumberField),
+}
+
+fn regex_match(field: TextField, at: usize, pattern: &str) -> Result<Filter> {
+    let regex = Regex::new(pattern).map_err(|e| syntax_error(at, format!("无效的正则：{}", e)))?;
+    Ok(Filter::Text(field, TextMatch::Regex(regex)))
+}
+
+// 本地时间转换为毫秒时间戳
+fn parse_time(s: &str) -> Result<i64> {
+    let datetime = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0))
+        .with_context(|| format!("无效的时间 {}，应为 YYYY-MM-DD [HH:MM[:SS]] 或毫秒时间戳", s))?;
+    let local = datetime
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .with_context(|| format!("本地时区中不存在时间 {}", s))?;
+    Ok(local.timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(user_id: i64, nickname: &str, content: &str, time: i64, liked: i32) -> Comment {
+        serde_json::from_value(serde_json::json!({
+            "commentId": 1,
+            "user": { "userId": user_id, "nickname": nickname, "avatarUrl": "" },
+            "content": content,
+            "time": time,
+            "likedCount": liked,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn evaluates_fields_and_boolean_combinations() {
+        let filter = Filter::parse("liked>=100 && content~/晚安/").unwrap();
+        assert!(filter.matches(&comment(1, "a", "大家晚安", 0, 100)));
+        assert!(!filter.matches(&comment(1, "a", "大家晚安", 0, 99)));
+        assert!(!filter.matches(&comment(1, "a", "早上好", 0, 500)));
+
+        let filter = Filter::parse(r#"(nickname~"猫" || uid==7) && !content~'广告' && time<1000"#).unwrap();
+        assert!(filter.matches(&comment(1, "小猫", "好听", 999, 0)));
+        assert!(filter.matches(&comment(7, "狗", "好听", 0, 0)));
+        assert!(!filter.matches(&comment(7, "狗", "看广告", 0, 0)));
+        assert!(!filter.matches(&comment(1, "小猫", "好听", 1000, 0)));
+
+        // 单独的字符串和正则按评论内容匹配，&& 优先于 ||
+        let filter = Filter::parse(r#""晚安" || /^早/ && liked>5"#).unwrap();
+        assert!(filter.matches(&comment(1, "a", "晚安", 0, 0)));
+        assert!(!filter.matches(&comment(1, "a", "早安", 0, 0)));
+        assert!(filter.matches(&comment(1, "a", "早安", 0, 6)));
+
+        let built = Filter::keyword("晚安").and(Filter::min_liked(10)).or(!Filter::nickname("x"));
+        assert!(built.matches(&comment(1, "x", "晚安", 0, 10)));
+        assert!(!built.matches(&comment(1, "x", "晚安", 0, 9)));
+    }
+
+    #[test]
+    fn display_round_trips_through_parse() {
+        let exprs = [
+            "liked>=100 && content~/晚\\/安/",
+            "(nickname==\"a\\\"b\" || user!=3) && !(time<5 && content~\"x\")",
+            "!content~/\\d+/ || liked==-1",
+        ];
+        for expr in exprs {
+            let filter = Filter::parse(expr).unwrap();
+            assert_eq!(filter.to_string(), expr);
+            assert_eq!(Filter::parse(&filter.to_string()).unwrap().to_string(), expr);
+        }
+        assert_eq!(Filter::parse("content !~ \"x\"").unwrap().to_string(), "!content~\"x\"");
+    }
+
+    #[test]
+    fn parses_local_dates_for_time() {
+        let filter = Filter::parse(r#"time>="2024-01-02" && time<"2024-01-02 08:30""#).unwrap();
+        let midnight = parse_time("2024-01-02 00:00:00").unwrap();
+        assert!(filter.matches(&comment(1, "a", "", midnight, 0)));
+        assert!(filter.matches(&comment(1, "a", "", midnight + 8 * 3600 * 1000, 0)));
+        assert!(!filter.matches(&comment(1, "a", "", midnight - 1, 0)));
+        assert!(!filter.matches(&comment(1, "a", "", midnight + 8 * 3600 * 1000 + 30 * 60 * 1000, 0)));
+    }
+
+    #[test]
+    fn reports_position_of_syntax_errors() {
+        let error = |expr: &str| Filter::parse(expr).unwrap_err().to_string();
+        assert!(error("liked>=").contains("第 8 个字符"));
+        assert!(error("likes>1").contains("未知字段 likes"));
+        assert!(error("liked~\"1\"").contains("不支持比较符 ~"));
+        assert!(error("content~/(/").contains("无效的正则"));
+        assert!(error("(liked>1").contains("表达式不完整"));
+        assert!(error("liked>1 liked<2").contains("多余的 liked"));
+        assert!(error("time>\"昨天\"").contains("无效的时间"));
+    }
+}
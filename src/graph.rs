@@ -0,0 +1,280 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use tokio::time::{sleep, Duration};
+
+use crate::api::MusicApi;
+use crate::output;
+use crate::types::{Follow, Profile};
+use crate::REQUEST_DELAY_MS;
+
+// 每页获取的关注/粉丝数
+const GRAPH_PAGE_SIZE: i32 = 100;
+
+// 扩展方向：关注、粉丝或两者
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum Direction {
+    Follows,
+    Followeds,
+    Both,
+}
+
+impl Direction {
+    fn follows(self) -> bool {
+        matches!(self, Direction::Follows | Direction::Both)
+    }
+
+    fn followeds(self) -> bool {
+        matches!(self, Direction::Followeds | Direction::Both)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphCrawlOptions {
+    // 从种子用户出发的最大扩展层数
+    pub depth: u32,
+    // 图中最多保存的节点数
+    pub max_nodes: usize,
+    // 每个用户最多读取的关注/粉丝数
+    pub max_neighbors: usize,
+    pub direction: Direction,
+    pub concurrency: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub user_id: i64,
+    pub nickname: String,
+    pub avatar_url: String,
+    pub signature: Option<String>,
+    pub followeds: i32,
+    pub vip_type: i32,
+    pub province: Option<i32>,
+    pub city: Option<i32>,
+    // 距离最近种子用户的层数
+    pub depth: u32,
+    // 是否已经读取过该用户的关注/粉丝
+    pub expanded: bool,
+}
+
+impl GraphNode {
+    fn from_follow(follow: &Follow, depth: u32) -> Self {
+        Self {
+            user_id: follow.user_id,
+            nickname: follow.nickname.clone(),
+            avatar_url: follow.avatar_url.clone(),
+            signature: follow.signature.clone(),
+            followeds: follow.followeds,
+            vip_type: follow.vip_type,
+            province: None,
+            city: None,
+            depth,
+            expanded: false,
+        }
+    }
+
+    fn merge_profile(&mut self, profile: &Profile) {
+        self.nickname = profile.nickname.clone();
+        self.avatar_url = profile.avatar_url.clone();
+        self.signature = profile.signature.clone();
+        self.followeds = profile.followeds;
+        self.vip_type = profile.vip_type;
+        self.province = Some(profile.province);
+        self.city = Some(profile.city);
+    }
+}
+
+// 有向边：source 关注了 target
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: i64,
+    pub target: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SocialGraph {
+    pub nodes: BTreeMap<i64, GraphNode>,
+    pub edges: BTreeSet<GraphEdge>,
+}
+
+impl SocialGraph {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        output::write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+// 单个用户展开的结果
+struct Expansion {
+    uid: i64,
+    profile: Option<Profile>,
+    follows: Vec<Follow>,
+    followeds: Vec<Follow>,
+}
+
+// 分页读取用户的关注列表
+async fn collect_follows<A: MusicApi + ?Sized>(api: &A, uid: i64, max: usize) -> Result<Vec<Follow>> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while result.len() < max {
+        sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+        let response = api.get_user_follows(uid, Some(GRAPH_PAGE_SIZE), Some(offset)).await?;
+        let count = response.follow.len();
+        result.extend(response.follow);
+        if !response.more || count == 0 {
+            break;
+        }
+        offset += GRAPH_PAGE_SIZE;
+    }
+    result.truncate(max);
+    Ok(result)
+}
+
+// 分页读取用户的粉丝列表
+async fn collect_followeds<A: MusicApi + ?Sized>(api: &A, uid: i64, max: usize) -> Result<Vec<Follow>> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while result.len() < max {
+        sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+        let response = api.get_user_followeds(uid, Some(GRAPH_PAGE_SIZE), Some(offset)).await?;
+        let count = response.followeds.len();
+        result.extend(response.followeds);
+        if !response.more || count == 0 {
+            break;
+        }
+        offset += GRAPH_PAGE_SIZE;
+    }
+    result.truncate(max);
+    Ok(result)
+}
+
+#[tracing::instrument(name = "user", skip(api, options))]
+async fn expand_user<A: MusicApi + ?Sized>(api: &A, uid: i64, options: &GraphCrawlOptions) -> Result<Expansion> {
+    sleep(Duration::from_millis(REQUEST_DELAY_MS)).await;
+    let profile = match api.get_user_profile(uid).await {
+        Ok(profile) => Some(profile.profile),
+        Err(e) => {
+            tracing::warn!(uid, error = %e, "获取用户详情失败");
+            None
+        }
+    };
+    let follows = if options.direction.follows() {
+        collect_follows(api, uid, options.max_neighbors).await?
+    } else {
+        Vec::new()
+    };
+    let followeds = if options.direction.followeds() {
+        collect_followeds(api, uid, options.max_neighbors).await?
+    } else {
+        Vec::new()
+    };
+    Ok(Expansion {
+        uid,
+        profile,
+        follows,
+        followeds,
+    })
+}
+
+// 基于 MusicApi 的关系图抓取，所有实现了 MusicApi 的类型都可以使用
+#[async_trait]
+pub trait GraphCrawler: MusicApi {
+    // 从种子用户出发按层 BFS 抓取关注/粉丝关系图
+    async fn crawl_social_graph(&self, seeds: &[i64], options: &GraphCrawlOptions) -> Result<SocialGraph> {
+        let mut graph = SocialGraph::default();
+        let mut queue: VecDeque<i64> = VecDeque::new();
+
+        for &uid in seeds {
+            if graph.nodes.len() >= options.max_nodes {
+                break;
+            }
+            if graph.nodes.contains_key(&uid) {
+                continue;
+            }
+            graph.nodes.insert(
+                uid,
+                GraphNode {
+                    user_id: uid,
+                    nickname: String::new(),
+                    avatar_url: String::new(),
+                    signature: None,
+                    followeds: 0,
+                    vip_type: 0,
+                    province: None,
+                    city: None,
+                    depth: 0,
+                    expanded: false,
+                },
+            );
+            queue.push_back(uid);
+        }
+
+        let mut current_depth = 0;
+        while !queue.is_empty() && current_depth < options.depth {
+            let level: Vec<i64> = queue.drain(..).collect();
+            tracing::info!(depth = current_depth + 1, users = level.len(), "展开新的一层");
+
+            let mut expansions = stream::iter(level)
+                .map(|uid| async move { (uid, expand_user(self, uid, options).await) })
+                .buffer_unordered(options.concurrency.max(1));
+
+            let mut next_level = HashSet::new();
+            while let Some((uid, result)) = expansions.next().await {
+                let expansion = match result {
+                    Ok(expansion) => expansion,
+                    Err(e) => {
+                        tracing::error!(uid, error = %e, "展开用户失败");
+                        continue;
+                    }
+                };
+
+                if let Some(node) = graph.nodes.get_mut(&expansion.uid) {
+                    if let Some(profile) = &expansion.profile {
+                        node.merge_profile(profile);
+                    }
+                    node.expanded = true;
+                }
+
+                let neighbors = expansion
+                    .follows
+                    .iter()
+                    .map(|f| (f, GraphEdge { source: expansion.uid, target: f.user_id }))
+                    .chain(
+                        expansion
+                            .followeds
+                            .iter()
+                            .map(|f| (f, GraphEdge { source: f.user_id, target: expansion.uid })),
+                    );
+
+                for (follow, edge) in neighbors {
+                    if !graph.nodes.contains_key(&follow.user_id) {
+                        if graph.nodes.len() >= options.max_nodes {
+                            continue;
+                        }
+                        graph
+                            .nodes
+                            .insert(follow.user_id, GraphNode::from_follow(follow, current_depth + 1));
+                        next_level.insert(follow.user_id);
+                    }
+                    graph.edges.insert(edge);
+                }
+            }
+
+            queue.extend(next_level);
+            current_depth += 1;
+        }
+
+        tracing::info!(nodes = graph.nodes.len(), edges = graph.edges.len(), "关系图抓取完成");
+        Ok(graph)
+    }
+}
+
+impl<T: MusicApi + ?Sized> GraphCrawler for T {}
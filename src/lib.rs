@@ -0,0 +1,45 @@
+// 网易云音乐 API 客户端：用户资料、听歌榜单、歌单、关注关系以及歌曲、歌单、专辑等资源的评论抓取
+
+pub mod api;
+pub mod cache;
+pub mod cassette;
+pub mod client;
+pub mod comment_stream;
+pub mod crawler;
+pub mod crypto;
+pub mod direct;
+pub mod dump;
+pub mod export;
+pub mod filter;
+pub mod graph;
+pub mod mentions;
+pub mod metadata;
+#[cfg(feature = "mock-server")]
+pub mod mock_server;
+pub mod output;
+pub mod progress;
+pub mod proxy;
+pub mod report;
+pub mod retry;
+pub mod transport;
+pub mod types;
+
+pub use api::{Backend, MusicApi};
+pub use client::NeteaseMusicClient;
+pub use comment_stream::{CommentStreamExt, CommentStreamOptions};
+pub use crawler::{CommentMatch, CommentMatcher, CrawlOptions, SongCrawler, SongScan};
+pub use progress::ProgressMode;
+pub use report::CrawlReport;
+pub use retry::{RetryEntry, RetryQueue};
+pub use graph::GraphCrawler;
+pub use direct::DirectTransport;
+pub use dump::{CommentDumper, DumpFormat};
+pub use filter::Filter;
+pub use mentions::{MentionKind, MentionTarget};
+pub use transport::{HttpConfig, HttpRequest, HttpResponse, MockTransport, ReqwestTransport, Transport};
+pub use types::*;
+
+pub const API_BASE_URL: &str = "https://netease-delta-ten.vercel.app";
+
+// 相邻两次请求之间的间隔，避免触发接口限流
+pub const REQUEST_DELAY_MS: u64 = 50;
@@ -1,808 +1,606 @@
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use clap::{Parser, Subcommand};
 use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use qr2term::print_qr;
-use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
-use base64::Engine;
-use chrono::{TimeZone, Local};
-
-const API_BASE_URL: &str = "https://netease-delta-ten.vercel.app";
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct LoginResponse {
-    code: i32,
-    #[serde(default)]
-    cookie: String,
-    #[serde(default)]
-    token: String,
-    account: Option<Account>,
-    profile: Option<Profile>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Account {
-    id: i64,
-    #[serde(rename = "userName")]
-    user_name: String,
-    #[serde(rename = "type")]
-    account_type: i32,
-    status: i32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct UserProfile {
-    code: i32,
-    profile: Profile,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Profile {
-    nickname: String,
-    #[serde(rename = "userId")]
-    user_id: i64,
-    #[serde(rename = "avatarUrl")]
-    avatar_url: String,
-    #[serde(rename = "backgroundUrl")]
-    background_url: Option<String>,
-    signature: Option<String>,
-    #[serde(rename = "createTime")]
-    create_time: i64,
-    #[serde(rename = "userName")]
-    #[serde(default)]
-    user_name: String,
-    #[serde(rename = "accountType")]
-    #[serde(default)]
-    account_type: i32,
-    #[serde(rename = "vipType")]
-    #[serde(default)]
-    vip_type: i32,
-    #[serde(default)]
-    followed: bool,
-    #[serde(default)]
-    follows: i32,
-    #[serde(default)]
-    followeds: i32,
-    #[serde(rename = "eventCount")]
-    #[serde(default)]
-    event_count: i32,
-    #[serde(rename = "playlistCount")]
-    #[serde(default)]
-    playlist_count: i32,
-    #[serde(rename = "playlistBeSubscribedCount")]
-    #[serde(default)]
-    playlist_be_subscribed_count: i32,
-    #[serde(default)]
-    province: i32,
-    #[serde(default)]
-    city: i32,
-    #[serde(default)]
-    birthday: i64,
-    #[serde(default)]
-    gender: i32,
-    description: Option<String>,
-    #[serde(rename = "detailDescription")]
-    detail_description: Option<String>,
-    #[serde(rename = "defaultAvatar")]
-    #[serde(default)]
-    default_avatar: bool,
-    #[serde(rename = "expertTags")]
-    expert_tags: Option<Vec<String>>,
-    experts: Option<serde_json::Value>,
-    #[serde(rename = "djStatus")]
-    #[serde(default)]
-    dj_status: i32,
-    #[serde(default)]
-    mutual: bool,
-    #[serde(rename = "remarkName")]
-    remark_name: Option<String>,
-    #[serde(rename = "authStatus")]
-    #[serde(default)]
-    auth_status: i32,
-    #[serde(default)]
-    blacklist: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct UserRecord {
-    code: i32,
-    #[serde(rename = "allData")]
-    all_data: Vec<SongData>,
-}
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use netease_music_api::cache::{CachingTransport, ResponseCache};
+use netease_music_api::cassette::{RecordingTransport, ReplayTransport};
+use netease_music_api::direct::DIRECT_BASE_URL;
+use netease_music_api::proxy::ProxyPoolTransport;
+#[cfg(feature = "mock-server")]
+use netease_music_api::mock_server::{self, MockServerConfig};
+use netease_music_api::{
+    dump, export, graph, metadata, output, Backend, CommentDumper, CommentOutput, CommentStreamExt, CrawlOptions,
+    DirectTransport, DumpFormat, Filter, GraphCrawler, HttpConfig, LoginResponse, MusicApi, NeteaseMusicClient,
+    ProgressMode, ReqwestTransport, ResourceType, RetryQueue, Song, SongCrawler, SongInfoOutput, Transport,
+    API_BASE_URL,
+};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct SongData {
-    score: i64,
-    song: Song,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Song {
-    name: String,
-    id: i64,
-    // 添加其他需要的字段
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct PlaylistResponse {
-    code: i32,
-    playlist: Vec<Playlist>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Playlist {
-    id: i64,
-    name: String,
-    #[serde(rename = "trackCount")]
-    track_count: i32,
-    #[serde(rename = "playCount")]
-    play_count: i64,
-    creator: Creator,
-    description: Option<String>,
-    tags: Vec<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Creator {
-    nickname: String,
-    #[serde(rename = "userId")]
-    user_id: i64,
-    #[serde(rename = "avatarUrl")]
-    avatar_url: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Comment {
-    commentId: i64,
-    user: CommentUser,
-    content: String,
-    time: i64,
-    likedCount: i32,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct CommentUser {
-    userId: i64,
-    nickname: String,
-    avatarUrl: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct UserComment {
-    song_id: i64,
-    song_name: String,
-    comment_id: i64,
-    content: String,
-    time: i64,
-    liked_count: i32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct FollowsResponse {
-    code: i32,
-    #[serde(default)]
-    #[serde(rename = "followeds")]
-    follow: Vec<Follow>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Follow {
-    nickname: String,
-    userId: i64,
-    avatarUrl: String,
-    signature: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CommentResponse {
-    code: i32,
-    comments: Vec<Comment>,
-    total: i32,
-}
-
-// 添加新的结构体用于二维码登录
-#[derive(Debug, Serialize, Deserialize)]
-struct QrKeyResponse {
-    code: i32,
-    #[serde(rename = "data")]
-    key_data: QrKeyData,
+async fn get_user_input(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct QrKeyData {
-    #[serde(rename = "unikey")]
-    key: String,
-    qrimg: Option<String>,
+#[derive(Parser)]
+#[command(about = "网易云音乐用户数据抓取工具")]
+struct Cli {
+    #[command(flatten)]
+    client: ClientArgs,
+    #[command(flatten)]
+    log: LogArgs,
+    #[command(flatten)]
+    crawl: CrawlArgs,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct QrCreateResponse {
-    code: i32,
-    #[serde(rename = "data")]
-    data: QrCreateData,
+// 评论抓取的进度输出和筛选参数
+#[derive(clap::Args)]
+struct CrawlArgs {
+    #[arg(long, global = true, value_enum, default_value_t = ProgressMode::Bars, help = "进度输出方式：bars 终端进度条，json 定期向 stderr 输出 JSON，quiet 不输出")]
+    progress: ProgressMode,
+    #[arg(short, long, global = true, help = "不显示进度，等同于 --progress quiet")]
+    quiet: bool,
+    #[arg(long, global = true, default_value_t = 10, help = "同时显示的单曲进度条上限")]
+    max_bars: usize,
+    #[arg(long = "where", global = true, value_name = "EXPR", help = "评论筛选表达式，例如 \"liked>=100 && content~/晚安/\"，字段有 content、nickname、liked、time、user")]
+    filter: Option<Filter>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct QrCreateData {
-    qrurl: String,
-    qrimg: Option<String>,
+impl CrawlArgs {
+    fn crawl_options(&self) -> CrawlOptions {
+        CrawlOptions {
+            progress: if self.quiet { ProgressMode::Quiet } else { self.progress },
+            max_visible_bars: self.max_bars,
+            filter: self.filter.clone(),
+            mentions: false,
+            hot_first: false,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct QrCheckResponse {
-    code: i32,
-    message: String,
-    cookie: Option<String>,
+// 日志输出参数
+#[derive(clap::Args)]
+struct LogArgs {
+    #[arg(long, global = true, default_value = "info", help = "终端日志级别，也可以写成过滤规则，例如 netease_music_api=debug")]
+    log_level: String,
+    #[arg(long, global = true, help = "把 JSON 格式的日志追加写入该文件")]
+    log_file: Option<PathBuf>,
+    #[arg(long, global = true, default_value = "info,netease_music_api=debug", help = "日志文件的级别或过滤规则")]
+    log_file_level: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CommentOutput {
-    用户: UserInfo,
-    被回复: Vec<String>,
-    挂件数据: Option<serde_json::Value>,
-    显示楼层评论: Option<serde_json::Value>,
-    状态: i32,
-    评论ID: i64,
-    内容: String,
-    富文本内容: Option<serde_json::Value>,
-    内容资源: Option<serde_json::Value>,
-    时间: i64,
-    时间字符串: String,
-    需要显示时间: bool,
-    点赞数: i32,
-    表情链接: Option<serde_json::Value>,
-    评论位置类型: i32,
-    父评论ID: i64,
-    装饰: serde_json::Map<String, serde_json::Value>,
-    回复标记: Option<serde_json::Value>,
-    等级: Option<serde_json::Value>,
-    用户业务等级: Option<serde_json::Value>,
-    IP位置: IpLocation,
+impl LogArgs {
+    // 终端输出可读格式，日志文件输出带 span 字段的 JSON，两者分别过滤
+    fn init(&self) -> Result<()> {
+        let console = tracing_subscriber::fmt::layer()
+            .with_writer(io::stderr)
+            .with_target(false)
+            .with_filter(EnvFilter::try_new(&self.log_level).context("无效的日志级别")?);
+        let file = match &self.log_file {
+            Some(path) => {
+                let file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("无法打开日志文件 {}", path.display()))?;
+                Some(
+                    tracing_subscriber::fmt::layer()
+                        .json()
+                        .with_current_span(true)
+                        .with_span_list(true)
+                        .with_writer(Mutex::new(file))
+                        .with_filter(EnvFilter::try_new(&self.log_file_level).context("无效的日志文件级别")?),
+                )
+            }
+            None => None,
+        };
+        tracing_subscriber::registry().with(console).with(file).init();
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct UserInfo {
-    地理位置: Option<serde_json::Value>,
-    直播信息: Option<serde_json::Value>,
-    是否匿名: i32,
-    头像详情: Option<serde_json::Value>,
-    用户类型: i32,
-    头像链接: String,
-    是否关注: bool,
-    是否互相关注: bool,
-    备注名: Option<String>,
-    社交用户ID: Option<serde_json::Value>,
-    会员权益: VipInfo,
-    昵称: String,
-    认证状态: i32,
-    专家标签: Option<serde_json::Value>,
-    专家: Option<serde_json::Value>,
-    会员类型: i32,
-    通用身份: Option<serde_json::Value>,
-    用户ID: i64,
+// 创建 NeteaseMusicClient 时使用的全局参数
+#[derive(clap::Args)]
+struct ClientArgs {
+    #[arg(long, global = true, conflicts_with = "replay", help = "将所有请求和响应（cookie 已脱敏）录制到该目录")]
+    record: Option<PathBuf>,
+    #[arg(long, global = true, help = "从录制目录回放响应，不访问网络")]
+    replay: Option<PathBuf>,
+    #[arg(long, global = true, help = "启用磁盘响应缓存")]
+    cache: bool,
+    #[arg(long, global = true, default_value = ".cache/responses", help = "响应缓存目录")]
+    cache_dir: PathBuf,
+    #[arg(long, global = true, value_parser = parse_ttl, help = "覆盖接口缓存时间，例如 /comment/music=3600，0 表示不缓存")]
+    cache_ttl: Vec<(String, u64)>,
+    #[arg(long, global = true, help = "离线模式：只从缓存读取，未命中时报错，等同于 --backend cache")]
+    offline: bool,
+    #[arg(long, global = true, value_enum, default_value_t = Backend::ProxyApi, help = "数据来源：proxy-api 经由 NeteaseCloudMusicApi，direct 直接请求 music.163.com，cache 只读缓存，mock 使用本地模拟数据")]
+    backend: Backend,
+    #[arg(long, global = true, default_value = "tests/fixtures/mock", help = "mock 后端使用的模拟数据目录")]
+    mock_fixtures: PathBuf,
+    #[arg(long, global = true, help = "代理地址（http/https/socks5），多次指定时轮询使用")]
+    proxy: Vec<String>,
+    #[arg(long, global = true, help = "代理列表文件，每行一个代理地址")]
+    proxy_file: Option<PathBuf>,
+    #[arg(long, global = true, default_value_t = 60, help = "代理出错或被限流后暂停使用的秒数")]
+    proxy_eject_secs: u64,
+    #[arg(long, global = true, default_value_t = 10, help = "连接超时（秒）")]
+    connect_timeout_secs: u64,
+    #[arg(long, global = true, default_value_t = 30, help = "单个请求的总时限（秒），从发出请求到读完响应，不是读取空闲超时")]
+    timeout_secs: u64,
+    #[arg(long, global = true, help = "自定义 User-Agent")]
+    user_agent: Option<String>,
+    #[arg(long = "header", global = true, value_parser = parse_header, help = "附加请求头，例如 \"X-Real-IP: 1.2.3.4\"，可多次指定")]
+    headers: Vec<(String, String)>,
+    #[arg(long, global = true, help = "附加到所有请求上的 realIP 参数，海外服务器需要设置")]
+    real_ip: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct VipInfo {
-    associator: Option<serde_json::Value>,
-    musicPackage: Option<serde_json::Value>,
-    redplus: Option<serde_json::Value>,
-    redVipAnnualCount: i32,
-    redVipLevel: i32,
-    relationType: i32,
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s.split_once(':').ok_or("格式应为 <名称>: <值>")?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct IpLocation {
-    IP: Option<String>,
-    地理位置: String,
-    用户ID: Option<serde_json::Value>,
+// <类型>:<ID>，类型见 ResourceType，例如 album:32311、event:32953014:6559519868
+fn parse_resource(s: &str) -> Result<(ResourceType, i64), String> {
+    let (resource, id) = s.rsplit_once(':').ok_or("格式应为 <类型>:<ID>")?;
+    let resource = resource.parse::<ResourceType>().map_err(|e| e.to_string())?;
+    let id = id.parse().map_err(|_| format!("无效的资源 ID: {}", id))?;
+    Ok((resource, id))
 }
 
-struct NeteaseMusicClient {
-    client: reqwest::Client,
-    cookie: Option<String>,
+fn parse_ttl(s: &str) -> Result<(String, u64), String> {
+    let (path, secs) = s.split_once('=').ok_or("格式应为 <接口>=<秒数>")?;
+    let secs = secs.parse().map_err(|_| format!("无效的秒数: {}", secs))?;
+    Ok((path.to_string(), secs))
 }
 
-impl NeteaseMusicClient {
-    fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            cookie: None,
+impl ClientArgs {
+    fn response_cache(&self) -> ResponseCache {
+        let mut cache = ResponseCache::new(&self.cache_dir);
+        for (path, secs) in &self.cache_ttl {
+            cache.set_ttl(path, Duration::from_secs(*secs));
         }
+        cache
     }
 
-    async fn login(&mut self, phone: &str, password: &str) -> Result<()> {
-        let url = format!("{}/login/cellphone", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[("phone", phone), ("password", password)])
-            .send()
-            .await?;
-
-        let cookies: Vec<String> = response
-            .headers()
-            .get_all("set-cookie")
-            .iter()
-            .filter_map(|v| v.to_str().ok())
-            .map(|s| s.to_string())
-            .collect();
-
-        let response_data = response.json::<LoginResponse>().await?;
-
-        if response_data.code == 200 {
-            let cookie = cookies.join("; ");
-            self.cookie = Some(cookie.clone());
-            
-            let response_with_cookie = LoginResponse {
-                cookie,
-                ..response_data
-            };
-            
-            fs::write("login_info.json", serde_json::to_string(&response_with_cookie)?)?;
-            println!("登录成功！");
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("登录失败：状态码 {}", response_data.code))
+    fn proxies(&self) -> Result<Vec<String>> {
+        let mut proxies = self.proxy.clone();
+        if let Some(file) = &self.proxy_file {
+            let content = fs::read_to_string(file)
+                .with_context(|| format!("无法读取代理列表 {}", file.display()))?;
+            proxies.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
         }
+        Ok(proxies)
     }
 
-    async fn get_user_profile(&self, uid: i64) -> Result<UserProfile> {
-        let url = format!("{}/user/detail", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[("uid", uid.to_string())])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<UserProfile>()
-            .await?;
-
-        Ok(response)
+    fn http_config(&self) -> HttpConfig {
+        HttpConfig {
+            base_url: if self.backend() == Backend::Direct { DIRECT_BASE_URL } else { API_BASE_URL }.to_string(),
+            connect_timeout: Some(Duration::from_secs(self.connect_timeout_secs)),
+            request_timeout: Some(Duration::from_secs(self.timeout_secs)),
+            user_agent: self.user_agent.clone(),
+            headers: self.headers.clone(),
+            real_ip: self.real_ip.clone(),
+            ..HttpConfig::default()
+        }
     }
 
-    async fn get_user_record(&self, uid: i64) -> Result<UserRecord> {
-        let url = format!("{}/user/record", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[("uid", uid.to_string()), ("type", "0".to_string())])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<UserRecord>()
-            .await?;
-
-        Ok(response)
+    fn backend(&self) -> Backend {
+        if self.offline {
+            Backend::Cache
+        } else {
+            self.backend
+        }
     }
 
-    // 获取用户歌单
-    async fn get_user_playlists(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<PlaylistResponse> {
-        let url = format!("{}/user/playlist", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("uid", uid.to_string()),
-                ("limit", limit.unwrap_or(30).to_string()),
-                ("offset", offset.unwrap_or(0).to_string()),
-            ])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<PlaylistResponse>()
-            .await?;
-
-        Ok(response)
+    fn backend_transport(&self, config: &HttpConfig) -> Result<Arc<dyn Transport>> {
+        if self.backend() == Backend::Direct {
+            Ok(Arc::new(DirectTransport::with_config(config)?))
+        } else {
+            Ok(Arc::new(ReqwestTransport::with_config(config)?))
+        }
     }
 
-    // 获取用户关注列表
-    async fn get_user_follows(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<FollowsResponse> {
-        let url = format!("{}/user/follows", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("uid", uid.to_string()),
-                ("limit", limit.unwrap_or(30).to_string()),
-                ("offset", offset.unwrap_or(0).to_string()),
-            ])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<FollowsResponse>()
-            .await?;
-
-        Ok(response)
+    fn http_transport(&self) -> Result<Arc<dyn Transport>> {
+        let config = self.http_config();
+        let with_proxy = |proxy: &String| HttpConfig {
+            proxy: Some(proxy.clone()),
+            ..config.clone()
+        };
+        let proxies = self.proxies()?;
+        match proxies.as_slice() {
+            [] => self.backend_transport(&config),
+            [proxy] => self.backend_transport(&with_proxy(proxy)),
+            _ => {
+                let members = proxies
+                    .iter()
+                    .map(|proxy| Ok((proxy.clone(), self.backend_transport(&with_proxy(proxy))?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(ProxyPoolTransport::from_transports(
+                    members,
+                    Duration::from_secs(self.proxy_eject_secs),
+                )))
+            }
+        }
     }
 
-    // 获取用户粉丝列表
-    async fn get_user_followeds(&self, uid: i64, limit: Option<i32>, offset: Option<i32>) -> Result<FollowsResponse> {
-        let url = format!("{}/user/followeds", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("uid", uid.to_string()),
-                ("limit", limit.unwrap_or(30).to_string()),
-                ("offset", offset.unwrap_or(0).to_string()),
-            ])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<FollowsResponse>()
-            .await?;
-
-        Ok(response)
+    // 在本地随机端口启动模拟服务，经由 ReqwestTransport 访问
+    #[cfg(feature = "mock-server")]
+    async fn mock_transport(&self) -> Result<Arc<dyn Transport>> {
+        let addr = mock_server::spawn(MockServerConfig::new(&self.mock_fixtures), "127.0.0.1:0".parse()?).await?;
+        tracing::info!(fixtures = %self.mock_fixtures.display(), %addr, "使用模拟数据");
+        Ok(Arc::new(ReqwestTransport::new(format!("http://{}", addr))))
     }
 
-    // 关注/取消关注用户
-    async fn follow_user(&self, uid: i64, follow: bool) -> Result<serde_json::Value> {
-        let url = format!("{}/follow", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("id", uid.to_string()),
-                ("t", if follow { "1" } else { "0" }.to_string()),
-            ])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<serde_json::Value>()
-            .await?;
-
-        Ok(response)
+    #[cfg(not(feature = "mock-server"))]
+    async fn mock_transport(&self) -> Result<Arc<dyn Transport>> {
+        Err(anyhow::anyhow!("编译时未启用 mock-server 特性，无法使用 mock 后端"))
     }
 
-    // 获取歌曲评论
-    async fn get_song_comments(&self, song_id: i64, limit: i32, offset: i32) -> Result<CommentResponse> {
-        let url = format!("{}/comment/music", API_BASE_URL);
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("id", song_id.to_string()),
-                ("limit", limit.to_string()),
-                ("offset", offset.to_string()),
-            ])
-            .header("Cookie", self.cookie.as_ref().unwrap())
-            .send()
-            .await?
-            .json::<CommentResponse>()
-            .await?;
-
-        Ok(response)
+    async fn transport(&self) -> Result<Arc<dyn Transport>> {
+        let mut transport: Arc<dyn Transport> = match (&self.replay, self.backend()) {
+            (_, Backend::Cache) => return Ok(Arc::new(CachingTransport::offline(self.response_cache()))),
+            (Some(dir), _) => Arc::new(ReplayTransport::load(dir)?),
+            (None, Backend::Mock) => self.mock_transport().await?,
+            (None, _) => self.http_transport()?,
+        };
+        if let Some(dir) = &self.record {
+            transport = Arc::new(RecordingTransport::new(transport, dir)?);
+        }
+        if self.cache {
+            transport = Arc::new(CachingTransport::new(transport, self.response_cache()));
+        }
+        Ok(transport)
     }
+}
 
-    // 并发获取用户在歌曲下的评论
-    async fn get_user_comments_for_songs(&self, songs: &[SongData], target_uid: i64) -> Result<()> {
-        use futures::stream::{self, StreamExt};
-        use tokio::time::{sleep, Duration};
-
-        // 创建 comments 目录用于保存评论文件
-        fs::create_dir_all("comments")?;
-
-        // 设置并发数为 50
-        let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(50));
-
-        // 创建进度条
-        let m = MultiProgress::new();
-        let total_progress = std::sync::Arc::new(m.add(ProgressBar::new(songs.len() as u64)));
-        total_progress.set_style(
-            ProgressStyle::default_bar()
-                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} 首歌曲 ({percent}%)")
-                .unwrap_or_else(|_| ProgressStyle::default_bar())
-                .progress_chars("##-")
-        );
-
-        // 创建歌曲处理流
-        let song_stream = stream::iter(songs.iter().enumerate()).map({
-            let total_progress = total_progress.clone();
-            move |(song_index, song)| {
-                let client = self.clone();
-                let semaphore = semaphore.clone();
-                let total_progress = total_progress.clone();
-                let song_progress = m.add(ProgressBar::new(100));
-                
-                song_progress.set_style(
-                    ProgressStyle::default_bar()
-                        .template("[{elapsed_precise}] {prefix:.green} {bar:40.yellow/red} {pos}/{len} 页评论")
-                        .unwrap_or_else(|_| ProgressStyle::default_bar())
-                        .progress_chars("##-")
-                );
-                
-                song_progress.set_prefix(format!("歌曲 {}/{}", song_index + 1, songs.len()));
-
-                async move {
-                    let _permit = semaphore.acquire().await.unwrap();
-                    let mut song_comments = Vec::new();
-                    
-                    // 每页获取 100 条评论，最多获取 100 页
-                    for offset in (0..10000).step_by(100) {
-                        sleep(Duration::from_millis(50)).await;
-                        
-                        match client.get_song_comments(song.song.id, 100, offset).await {
-                            Ok(response) => {
-                                let comments = response.comments.clone();
-                                let user_comments: Vec<CommentOutput> = response.comments
-                                    .into_iter()
-                                    .filter(|comment| comment.user.userId == target_uid)
-                                    .map(|comment| {
-                                        let time = Local.timestamp_millis_opt(comment.time).unwrap();
-                                        CommentOutput {
-                                            用户: UserInfo {
-                                                地理位置: None,
-                                                直播信息: None,
-                                                是否匿名: 0,
-                                                头像详情: None,
-                                                用户类型: 0,
-                                                头像链接: comment.user.avatarUrl,
-                                                是否关注: false,
-                                                是否互相关注: false,
-                                                备注名: None,
-                                                社交用户ID: None,
-                                                会员权益: VipInfo {
-                                                    associator: None,
-                                                    musicPackage: None,
-                                                    redplus: None,
-                                                    redVipAnnualCount: -1,
-                                                    redVipLevel: 0,
-                                                    relationType: 0,
-                                                },
-                                                昵称: comment.user.nickname,
-                                                认证状态: 0,
-                                                专家标签: None,
-                                                专家: None,
-                                                会员类型: 0,
-                                                通用身份: None,
-                                                用户ID: comment.user.userId,
-                                            },
-                                            被回复: Vec::new(),
-                                            挂件数据: None,
-                                            显示楼层评论: None,
-                                            状态: 0,
-                                            评论ID: comment.commentId,
-                                            内容: comment.content,
-                                            富文本内容: None,
-                                            内容资源: None,
-                                            时间: comment.time,
-                                            时间字符串: time.format("%Y-%m-%d").to_string(),
-                                            需要显示时间: true,
-                                            点赞数: comment.likedCount,
-                                            表情链接: None,
-                                            评论位置类型: 0,
-                                            父评论ID: 0,
-                                            装饰: serde_json::Map::new(),
-                                            回复标记: None,
-                                            等级: None,
-                                            用户业务等级: None,
-                                            IP位置: IpLocation {
-                                                IP: None,
-                                                地理位置: String::new(),
-                                                用户ID: None,
-                                            },
-                                        }
-                                    })
-                                    .collect();
-                                
-                                song_comments.extend(user_comments);
-
-                                // 每 5 页更新一次进度条
-                                if offset % 500 == 0 {
-                                    song_progress.inc(5);
-                                }
-
-                                // 如果返回的评论数小于请求数，说明已到达末尾
-                                if comments.len() < 100 {
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!("获取歌曲 {} 的评论失败: {}", song.song.id, e);
-                                break;
-                            }
-                        }
-                    }
-
-                    // 保存当前歌曲的评论到单独的文件
-                    if !song_comments.is_empty() {
-                        if let Ok(json_str) = serde_json::to_string_pretty(&song_comments) {
-                            let file_path = format!("comments/song_{}.json", song.song.id);
-                            if let Err(e) = fs::write(&file_path, json_str) {
-                                eprintln!("保存评论文件失败: {}", e);
-                            }
-                        }
-                    }
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "从一个或多个用户出发抓取关注/粉丝关系图")]
+    GraphCrawl {
+        #[arg(required = true, help = "种子用户 UID")]
+        uids: Vec<i64>,
+        #[arg(long, default_value_t = 2, help = "最大扩展层数")]
+        depth: u32,
+        #[arg(long, default_value_t = 1000, help = "最多保存的节点数")]
+        max_nodes: usize,
+        #[arg(long, default_value_t = 500, help = "每个用户最多读取的关注/粉丝数")]
+        max_neighbors: usize,
+        #[arg(long, value_enum, default_value_t = graph::Direction::Both, help = "扩展方向")]
+        direction: graph::Direction,
+        #[arg(long, default_value_t = 5, help = "同时展开的用户数")]
+        concurrency: usize,
+        #[arg(long, default_value = "graph/graph.json", help = "关系图保存路径")]
+        output: PathBuf,
+    },
+    #[command(about = "将抓取到的关系图导出为 GraphML、GEXF 或 DOT")]
+    GraphExport {
+        #[arg(long, default_value = "graph/graph.json", help = "graph-crawl 保存的关系图")]
+        input: PathBuf,
+        #[arg(long, value_enum, default_value_t = export::GraphFormat::Graphml, help = "导出格式")]
+        format: export::GraphFormat,
+        #[arg(long, help = "导出文件路径，默认与输入同名并使用对应扩展名")]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "在一个用户听歌榜单的歌曲下，一次翻页同时查找多个用户的评论")]
+    Comments {
+        #[arg(long = "uid", help = "要查找评论的用户 UID，可重复指定")]
+        uids: Vec<i64>,
+        #[arg(long, help = "从文件读取用户 UID，每行一个，# 开头的行忽略")]
+        uids_file: Option<PathBuf>,
+        #[arg(long, help = "使用该用户的听歌榜单作为歌曲列表，默认取第一个目标用户")]
+        songs_of: Option<i64>,
+        #[arg(
+            long = "resource",
+            value_parser = parse_resource,
+            conflicts_with = "songs_of",
+            help = "改为在指定资源的评论区中查找，格式 <类型>:<ID>，例如 album:32311、event:<发布者UID>:<动态ID>，可重复指定"
+        )]
+        resources: Vec<(ResourceType, i64)>,
+        #[arg(long, help = "同时查找其他人 @ 或回复目标用户的评论，保存到 comments/user_<UID>/mentions/")]
+        mentions: bool,
+        #[arg(long, help = "先检查热门评论，所有目标用户都在其中时跳过翻页，会漏掉不在热门评论中的评论")]
+        hot_first: bool,
+    },
+    #[command(about = "获取歌曲、歌单、专辑等资源的热门评论")]
+    Hot {
+        #[arg(help = "资源 ID")]
+        id: i64,
+        #[arg(
+            long = "type",
+            default_value_t = ResourceType::Song,
+            help = "资源类型：song、playlist、album、mv、video、dj 或 event:<发布者UID>"
+        )]
+        resource: ResourceType,
+        #[arg(long, default_value_t = 50, help = "最多获取的热门评论数")]
+        limit: usize,
+        #[arg(long, help = "保存路径，默认 comments/hot/<类型>_<ID>.json")]
+        output: Option<PathBuf>,
+    },
+    #[command(about = "不筛选用户，把歌曲下的全部评论导出为 NDJSON 或 SQLite")]
+    Dump {
+        #[arg(long = "song", help = "歌曲 ID，可重复指定")]
+        songs: Vec<i64>,
+        #[arg(long, help = "导出该歌单中全部歌曲的评论")]
+        playlist: Option<i64>,
+        #[arg(long, help = "导出该用户听歌榜单中歌曲的评论")]
+        songs_of: Option<i64>,
+        #[arg(long, value_enum, default_value_t = DumpFormat::Ndjson, help = "导出格式")]
+        format: DumpFormat,
+        #[arg(long, help = "导出文件路径，默认 comments/dump 加对应扩展名")]
+        output: Option<PathBuf>,
+        #[arg(long, help = "每首歌最多导出的评论数，默认不限")]
+        max_comments: Option<usize>,
+    },
+    #[command(about = "重新抓取上次失败的歌曲，从失败的位置继续")]
+    RetryFailed {
+        #[arg(long, default_value = "comments/retry_queue.json", help = "重试队列文件")]
+        queue: PathBuf,
+    },
+    #[command(about = "查看或清理响应缓存")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
 
-                    song_progress.finish_with_message(format!("歌曲 {} 完成", song.song.name));
-                    total_progress.inc(1);
-                    
-                    Ok::<_, anyhow::Error>(song_comments)
-                }
-            }
-        });
-
-        // 并发处理所有歌曲，最多 50 个并发
-        let mut buffered = song_stream.buffer_unordered(50);
-        
-        while let Some(result) = buffered.next().await {
-            if let Ok(_comments) = result {
-                // 评论已经保存到文件，这里不需要额外处理
-            }
-        }
+#[derive(Subcommand)]
+enum CacheAction {
+    #[command(about = "按接口统计缓存条目")]
+    Stats,
+    #[command(about = "删除缓存条目")]
+    Purge {
+        #[arg(long, help = "只删除已过期的条目")]
+        expired: bool,
+        #[arg(long, help = "只删除该接口的条目，例如 /comment/music")]
+        endpoint: Option<String>,
+    },
+}
 
-        total_progress.finish_with_message("所有歌曲评论获取完成！");
+// 读取 UID 列表文件，每行一个，忽略空行和 # 开头的注释
+fn read_uid_file(path: &Path) -> Result<Vec<i64>> {
+    let content = fs::read_to_string(path).with_context(|| format!("无法读取 {}", path.display()))?;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse::<i64>().with_context(|| format!("无效的 UID: {}", line)))
+        .collect()
+}
 
-        Ok(())
+// 读取已保存的登录信息，没有则走二维码登录
+async fn logged_in_client(args: &ClientArgs) -> Result<NeteaseMusicClient> {
+    let mut client = NeteaseMusicClient::with_transport(args.transport().await?);
+    if !Path::new("login_info.json").exists() {
+        tracing::info!("没有已保存的登录信息，使用二维码登录");
+        client.login_by_qr().await?;
+    } else {
+        let login_info: LoginResponse = serde_json::from_str(&fs::read_to_string("login_info.json")?)?;
+        client.set_cookie(login_info.cookie);
+        tracing::info!("使用已保存的登录信息");
     }
+    Ok(client)
+}
 
-    // 获取二维码 key
-    async fn get_qr_key(&self) -> Result<String> {
-        let url = format!("{}/login/qr/key", API_BASE_URL);
-        let timestamp = chrono::Local::now().timestamp_millis().to_string();
-        let response = self
-            .client
-            .get(&url)
-            .query(&[("timestamp", &timestamp)])
-            .send()
-            .await?
-            .json::<QrKeyResponse>()
-            .await?;
-
-        if response.code == 200 {
-            Ok(response.key_data.key)
-        } else {
-            Err(anyhow::anyhow!("获取二维码key失败"))
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    cli.log.init()?;
+
+    match cli.command {
+        Some(Command::GraphCrawl {
+            uids,
+            depth,
+            max_nodes,
+            max_neighbors,
+            direction,
+            concurrency,
+            output,
+        }) => {
+            let client = logged_in_client(&cli.client).await?;
+            let options = graph::GraphCrawlOptions {
+                depth,
+                max_nodes,
+                max_neighbors,
+                direction,
+                concurrency,
+            };
+            let social_graph = client.crawl_social_graph(&uids, &options).await?;
+            social_graph.save(&output)?;
+            println!("关系图已保存到 {}", output.display());
+            Ok(())
         }
-    }
-
-    // 生成二维码
-    async fn create_qr(&self, key: &str) -> Result<String> {
-        let url = format!("{}/login/qr/create", API_BASE_URL);
-        let timestamp = chrono::Local::now().timestamp_millis().to_string();
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("key", key),
-                ("qrimg", "true"),
-                ("timestamp", &timestamp)
-            ])
-            .send()
-            .await?
-            .json::<QrCreateResponse>()
-            .await?;
-
-        if response.code == 200 {
-            if let Some(qr_img) = response.data.qrimg {
-                // 使用新的 base64 解码方法
-                let qr_data = BASE64_STANDARD.decode(
-                    qr_img.split(',').nth(1).unwrap_or("")
-                )?;
-                
-                // 克隆数据用于文件写入
-                fs::write("qr_code.png", &qr_data)?;
-                println!("\n二维码已保存到 qr_code.png");
-                
-                // 直接打印 URL 的二维码
-                print_qr(response.data.qrurl.as_bytes())?;
-                
-                Ok(response.data.qrurl)
+        Some(Command::GraphExport { input, format, output }) => {
+            // 导出只读取本地文件，不需要登录
+            let social_graph = graph::SocialGraph::load(&input)?;
+            let output = output.unwrap_or_else(|| input.with_extension(format.extension()));
+            fs::write(&output, social_graph.export(format))?;
+            println!("关系图已导出到 {}", output.display());
+            Ok(())
+        }
+        Some(Command::Comments {
+            mut uids,
+            uids_file,
+            songs_of,
+            resources,
+            mentions,
+            hot_first,
+        }) => {
+            if let Some(path) = uids_file {
+                uids.extend(read_uid_file(&path)?);
+            }
+            // --songs-of 只决定扫描哪些歌曲，没有目标用户时什么也匹配不到
+            if uids.is_empty() {
+                return Err(anyhow::anyhow!("至少需要一个目标用户，使用 --uid 或 --uids-file 指定"));
+            }
+            let songs_of = songs_of.unwrap_or(uids[0]);
+            let client = logged_in_client(&cli.client).await?;
+            let options = CrawlOptions {
+                mentions,
+                hot_first,
+                ..cli.crawl.crawl_options()
+            };
+            let report = if resources.is_empty() {
+                let record = client.get_user_record(songs_of).await?;
+                println!("用户 {} 的听歌榜单共 {} 首歌曲，查找 {} 个用户的评论", songs_of, record.all_data.len(), uids.len());
+                client.get_user_comments_for_songs(&record.all_data, &uids, &options).await?
             } else {
-                Ok(response.data.qrurl)
+                println!("在 {} 个评论区中查找 {} 个用户的评论", resources.len(), uids.len());
+                client.get_user_comments_for_resources(&resources, &uids, &options).await?
+            };
+            println!("\n{}", report);
+            println!("评论已按用户保存到 comments/user_<UID>/，报告已保存到 comments/report.json");
+            if mentions {
+                println!("提及目标用户的评论已保存到 comments/user_<UID>/mentions/");
             }
-        } else {
-            Err(anyhow::anyhow!("生成二维码失败"))
+            if report.has_failures() {
+                println!("失败的评论区已记入 comments/retry_queue.json，可运行 retry-failed 继续");
+                return Err(anyhow::anyhow!("{} 个评论区抓取失败", report.failed().count()));
+            }
+            Ok(())
         }
-    }
-
-    // 检查二维码状态
-    async fn check_qr(&self, key: &str) -> Result<QrCheckResponse> {
-        let url = format!("{}/login/qr/check", API_BASE_URL);
-        let timestamp = chrono::Local::now().timestamp_millis().to_string();
-        let response = self
-            .client
-            .get(&url)
-            .query(&[
-                ("key", key),
-                ("timestamp", &timestamp)
-            ])
-            .send()
-            .await?
-            .json::<QrCheckResponse>()
-            .await?;
-
-        Ok(response)
-    }
-
-    // 二维码登录流程
-    async fn login_by_qr(&mut self) -> Result<()> {
-        println!("开始二维码登录流程...");
-        
-        // 获取二维码 key
-        let key = self.get_qr_key().await?;
-        
-        // 生成二维码
-        let qr_img = self.create_qr(&key).await?;
-        println!("\n请使用网易云音乐 App 扫描二维码：\n{}", qr_img);
-        
-        // 循环检查扫码状态
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            let check_resp = self.check_qr(&key).await?;
-            
-            match check_resp.code {
-                800 => {
-                    println!("二维码已过期，请重新运行程序");
-                    return Err(anyhow::anyhow!("二维码已过期"));
-                }
-                801 => {
-                    println!("等待扫码中...");
-                }
-                802 => {
-                    println!("扫码成功，请在手机上确认登录");
-                }
-                803 => {
-                    println!("登录成功！");
-                    if let Some(cookie) = check_resp.cookie {
-                        self.cookie = Some(cookie.clone());
-                        // 保存登录信息
-                        let login_info = LoginResponse {
-                            code: 200,
-                            cookie,
-                            token: String::new(),
-                            account: None,
-                            profile: None,
-                        };
-                        fs::write("login_info.json", serde_json::to_string(&login_info)?)?;
-                        return Ok(());
+        Some(Command::Dump {
+            songs,
+            playlist,
+            songs_of,
+            format,
+            output,
+            max_comments,
+        }) => {
+            let client = logged_in_client(&cli.client).await?;
+            let mut targets: Vec<Song> = songs.into_iter().map(|id| Song::new(id, id.to_string())).collect();
+            if let Some(playlist) = playlist {
+                targets.extend(dump::playlist_songs(&client, playlist).await?);
+            }
+            if let Some(uid) = songs_of {
+                targets.extend(client.get_user_record(uid).await?.all_data.into_iter().map(|data| data.song));
+            }
+            let mut seen = std::collections::HashSet::new();
+            targets.retain(|song| seen.insert(song.id));
+            if targets.is_empty() {
+                return Err(anyhow::anyhow!("没有要导出的歌曲，使用 --song、--playlist 或 --songs-of 指定"));
+            }
+            if let Err(e) = metadata::enrich_songs(&client, &mut targets).await {
+                tracing::warn!(error = %e, "获取歌曲详情失败，歌曲信息只包含 ID 和歌名");
+            }
+            let output = output.unwrap_or_else(|| Path::new("comments/dump").with_extension(format.extension()));
+            let sink = dump::open_sink(format, &output)?;
+            let report = client
+                .dump_song_comments(&targets, sink, max_comments, &cli.crawl.crawl_options())
+                .await?;
+            report.save(Path::new("comments/dump_report.json"))?;
+            // 导出文件旁边记录各歌曲的信息，例如 comments/dump.songs.json
+            let songs_path = output.with_extension("songs.json");
+            let songs: Vec<SongInfoOutput> = targets.iter().map(SongInfoOutput::from).collect();
+            output::write_atomic(&songs_path, serde_json::to_string_pretty(&songs)?)?;
+            println!("\n{}", report);
+            println!(
+                "评论已导出到 {}，歌曲信息保存在 {}，报告已保存到 comments/dump_report.json",
+                output.display(),
+                songs_path.display()
+            );
+            if report.has_failures() {
+                return Err(anyhow::anyhow!("{} 首歌曲导出失败", report.failed().count()));
+            }
+            Ok(())
+        }
+        Some(Command::Hot {
+            id,
+            resource,
+            limit,
+            output,
+        }) => {
+            let client = logged_in_client(&cli.client).await?;
+            let comments = client.hot_comments(resource, id, limit).await?;
+            for (index, comment) in comments.iter().enumerate() {
+                println!(
+                    "{}. {}（{} 赞）：{}",
+                    index + 1,
+                    comment.user.nickname,
+                    comment.liked_count,
+                    comment.content
+                );
+            }
+            let output = output.unwrap_or_else(|| {
+                Path::new("comments/hot").join(format!("{}_{}.json", resource.as_str(), id))
+            });
+            let comments: Vec<CommentOutput> = comments.into_iter().map(CommentOutput::from).collect();
+            output::write_atomic(&output, serde_json::to_string_pretty(&comments)?)?;
+            println!("{} 条热门评论已保存到 {}", comments.len(), output.display());
+            Ok(())
+        }
+        Some(Command::RetryFailed { queue }) => {
+            if RetryQueue::load(&queue)?.is_empty() {
+                println!("重试队列为空，没有需要重新抓取的歌曲");
+                return Ok(());
+            }
+            let client = logged_in_client(&cli.client).await?;
+            let report = client.retry_failed_songs(&queue, &cli.crawl.crawl_options()).await?;
+            println!("\n{}", report);
+            println!("报告已保存到 comments/retry_report.json");
+            if report.has_failures() {
+                return Err(anyhow::anyhow!(
+                    "{} 首歌曲仍然失败，已保留在 {}",
+                    report.failed().count(),
+                    queue.display()
+                ));
+            }
+            Ok(())
+        }
+        Some(Command::Cache { action }) => {
+            let cache = cli.client.response_cache();
+            match action {
+                CacheAction::Stats => {
+                    let mut stats: Vec<_> = cache.stats()?.into_iter().collect();
+                    stats.sort_by(|a, b| a.0.cmp(&b.0));
+                    println!("缓存目录: {}", cache.dir().display());
+                    for (path, item) in stats {
+                        let ttl = cache
+                            .ttl(&path)
+                            .map(|ttl| format!("{}s", ttl.as_secs()))
+                            .unwrap_or_else(|| "不缓存".to_string());
+                        println!(
+                            "{}: {} 条（{} 条已过期），{} KB，缓存时间 {}",
+                            path,
+                            item.entries,
+                            item.expired,
+                            item.bytes / 1024,
+                            ttl
+                        );
                     }
                 }
-                _ => {
-                    println!("未知状态：{}", check_resp.message);
+                CacheAction::Purge { expired, endpoint } => {
+                    let removed = cache.purge(expired, endpoint.as_deref())?;
+                    println!("已删除 {} 条缓存", removed);
                 }
             }
+            Ok(())
         }
-    }
-}
-
-// 为 NeteaseMusicClient 实现 Clone
-impl Clone for NeteaseMusicClient {
-    fn clone(&self) -> Self {
-        Self {
-            client: self.client.clone(),
-            cookie: self.cookie.clone(),
+        None => {
+            let client = logged_in_client(&cli.client).await?;
+            run_interactive(&client, &cli.crawl.crawl_options()).await
         }
     }
 }
 
-async fn get_user_input(prompt: &str) -> Result<String> {
-    print!("{}", prompt);
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    Ok(input.trim().to_string())
-}
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    let mut client = NeteaseMusicClient::new();
-
-    // 检查是否存在保存的登录信息
-    if !Path::new("login_info.json").exists() {
-        println!("请使用二维码登录网易云音乐");
-        client.login_by_qr().await?;
-    } else {
-        let login_info: LoginResponse = serde_json::from_str(&fs::read_to_string("login_info.json")?)?;
-        client.cookie = Some(login_info.cookie);
-        println!("使用已保存的登录信息");
-    }
-
+async fn run_interactive(client: &dyn MusicApi, options: &CrawlOptions) -> Result<()> {
     let uid = get_user_input("请输入要查询的用户 UID: ").await?
         .parse::<i64>()
         .context("无效的 UID")?;
@@ -831,15 +629,26 @@ async fn main() -> Result<()> {
         );
     }
 
-    // 获取听歌榜单
-    let record = client.get_user_record(uid).await?;
+    // 获取听歌榜单，缺少歌手和专辑信息时补全
+    let mut record = client.get_user_record(uid).await?;
+    let mut songs: Vec<Song> = record.all_data.iter().map(|data| data.song.clone()).collect();
+    if let Err(e) = metadata::enrich_songs(client, &mut songs).await {
+        tracing::warn!(error = %e, "获取歌曲详情失败");
+    }
+    for (data, song) in record.all_data.iter_mut().zip(songs) {
+        data.song = song;
+    }
     println!("\n听歌榜单:");
     for (index, song_data) in record.all_data.iter().enumerate() {
+        let song = &song_data.song;
+        let album = song.album.as_ref().map(|a| format!("《{}》", a.name)).unwrap_or_default();
         println!(
-            "{}. {} (ID: {}) - 播放次数: {}",
+            "{}. {} - {}{} (ID: {}) - 播放次数: {}",
             index + 1,
-            song_data.song.name,
-            song_data.song.id,
+            song.name,
+            song.artist_names(),
+            album,
+            song.id,
             song_data.score
         );
     }
@@ -852,26 +661,32 @@ async fn main() -> Result<()> {
             "{}. {} (ID: {})",
             index + 1,
             follow.nickname,
-            follow.userId
+            follow.user_id
         );
     }
 
     // 获取用户粉丝列表
     let followeds = client.get_user_followeds(uid, Some(5), None).await?;
     println!("\n粉丝列表(前5个):");
-    for (index, followed) in followeds.follow.iter().enumerate() {
+    for (index, followed) in followeds.followeds.iter().enumerate() {
         println!(
             "{}. {} (ID: {}) {}",
             index + 1,
             followed.nickname,
-            followed.userId,
+            followed.user_id,
             followed.signature.as_deref().unwrap_or("")
         );
     }
 
     // 获取用户在这些歌曲下的评论
     println!("\n开始获取用户在这些歌曲下的评论...");
-    client.get_user_comments_for_songs(&record.all_data, uid).await?;
+    let report = client.get_user_comments_for_songs(&record.all_data, &[uid], options).await?;
+    println!("\n{}", report);
+    println!("评论已保存到 comments/user_{}/，报告已保存到 comments/report.json", uid);
+    if report.has_failures() {
+        println!("失败的歌曲已记入 comments/retry_queue.json，可运行 retry-failed 继续");
+        return Err(anyhow::anyhow!("{} 首歌曲抓取失败", report.failed().count()));
+    }
 
     Ok(())
 }
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::api::MusicApi;
+use crate::crawler::user_dir;
+use crate::output;
+use crate::types::{Comment, CommentOutput};
+
+// 其他人的评论提及目标用户的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MentionKind {
+    // 回复了目标用户的评论
+    Reply,
+    // 内容中 @ 了目标用户的昵称
+    At,
+}
+
+// 提及目标用户的一条评论，写入 comments/user_{uid}/mentions/song_{id}.json
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct MentionOutput {
+    pub 提及方式: MentionKind,
+    #[serde(flatten)]
+    pub 评论: CommentOutput,
+}
+
+impl MentionOutput {
+    pub fn new(kind: MentionKind, comment: CommentOutput) -> Self {
+        Self {
+            提及方式: kind,
+            评论: comment,
+        }
+    }
+}
+
+// 查找提及时使用的目标用户身份：用户 ID 和用过的昵称。
+// 昵称记录在 comments/user_{uid}/nicknames.json，每次解析时加入当前昵称，抓到本人评论时加入评论上的昵称
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MentionTarget {
+    pub uid: i64,
+    pub nicknames: BTreeSet<String>,
+}
+
+impl MentionTarget {
+    pub fn new(uid: i64, nicknames: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            uid,
+            nicknames: nicknames.into_iter().filter(|n| !n.is_empty()).collect(),
+        }
+    }
+
+    fn history_path(uid: i64) -> PathBuf {
+        user_dir(uid).join("nicknames.json")
+    }
+
+    // 读取已记录的昵称，文件不存在时昵称为空
+    pub fn load(uid: i64) -> Result<Self> {
+        let path = Self::history_path(uid);
+        if !path.exists() {
+            return Ok(Self::new(uid, []));
+        }
+        let nicknames: Vec<String> = serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("无法解析昵称记录 {}", path.display()))?;
+        Ok(Self::new(uid, nicknames))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        output::write_atomic(&Self::history_path(self.uid), serde_json::to_string_pretty(&self.nicknames)?)
+    }
+
+    // 加入一个昵称，返回是否是新的
+    pub fn insert(&mut self, nickname: &str) -> bool {
+        !nickname.is_empty() && self.nicknames.insert(nickname.to_string())
+    }
+
+    // 评论是否提及目标用户，本人的评论不算。既是回复又 @ 了目标时记为回复
+    pub fn mention(&self, comment: &Comment) -> Option<MentionKind> {
+        if comment.user.user_id == self.uid {
+            return None;
+        }
+        if comment.be_replied.iter().any(|r| r.user.user_id == self.uid) {
+            return Some(MentionKind::Reply);
+        }
+        self.nicknames
+            .iter()
+            .any(|nickname| mentions_nickname(&comment.content, nickname))
+            .then_some(MentionKind::At)
+    }
+}
+
+// content 中是否有 @nickname，且后面不紧跟字母、数字或汉字（避免 @小猫 匹配到 @小猫咪）
+fn mentions_nickname(content: &str, nickname: &str) -> bool {
+    let pattern = format!("@{}", nickname);
+    content.match_indices(&pattern).any(|(at, _)| {
+        content[at + pattern.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !c.is_alphanumeric())
+    })
+}
+
+// 解析目标用户的昵称：读取已记录的昵称，加上 get_user_profile 返回的当前昵称并保存。
+// 获取资料失败时只记录警告，仍可以按已记录的昵称和回复关系查找
+pub async fn resolve_mention_targets<A: MusicApi + ?Sized>(api: &A, uids: &[i64]) -> Result<Vec<MentionTarget>> {
+    let mut targets = Vec::with_capacity(uids.len());
+    for &uid in uids {
+        let mut target = MentionTarget::load(uid)?;
+        match api.get_user_profile(uid).await {
+            Ok(profile) if profile.code == 200 => {
+                if target.insert(&profile.profile.nickname) {
+                    target.save()?;
+                }
+            }
+            Ok(profile) => tracing::warn!(uid, code = profile.code, "获取用户资料失败，只按已记录的昵称查找提及"),
+            Err(e) => tracing::warn!(uid, error = %e, "获取用户资料失败，只按已记录的昵称查找提及"),
+        }
+        tracing::info!(uid, nicknames = ?target.nicknames, "查找提及该用户的评论");
+        targets.push(target);
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(user_id: i64, content: &str, replied_uid: Option<i64>) -> Comment {
+        let be_replied: Vec<_> = replied_uid
+            .map(|uid| serde_json::json!({ "user": { "userId": uid, "nickname": "x" }, "beRepliedCommentId": 1 }))
+            .into_iter()
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "commentId": 2,
+            "user": { "userId": user_id, "nickname": "n", "avatarUrl": "" },
+            "content": content,
+            "time": 0,
+            "likedCount": 0,
+            "beReplied": be_replied,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn detects_replies_and_at_mentions_of_any_known_nickname() {
+        let target = MentionTarget::new(7, ["小猫".to_string(), "old name".to_string()]);
+        assert_eq!(target.mention(&comment(1, "好听", Some(7))), Some(MentionKind::Reply));
+        assert_eq!(target.mention(&comment(1, "@小猫 晚安", Some(8))), Some(MentionKind::At));
+        assert_eq!(target.mention(&comment(1, "晚安@old name", None)), Some(MentionKind::At));
+        assert_eq!(target.mention(&comment(1, "@小猫：你也在", None)), Some(MentionKind::At));
+        assert_eq!(target.mention(&comment(1, "@小猫咪 晚安", None)), None);
+        assert_eq!(target.mention(&comment(1, "小猫 晚安", None)), None);
+        // 本人的评论不算提及
+        assert_eq!(target.mention(&comment(7, "@小猫", Some(7))), None);
+    }
+
+    #[test]
+    fn output_keeps_comment_fields_at_top_level() {
+        let output = MentionOutput::new(MentionKind::Reply, CommentOutput::from(comment(1, "好听", Some(7))));
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(json["提及方式"], "reply");
+        assert_eq!(json["评论ID"], 2);
+        assert_eq!(json["被回复"], serde_json::json!(["x"]));
+        let back: MentionOutput = serde_json::from_value(json).unwrap();
+        assert_eq!(back.评论.评论ID, 2);
+    }
+}
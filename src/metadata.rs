@@ -0,0 +1,39 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::api::MusicApi;
+use crate::types::Song;
+
+// /song/detail 一次最多查询的歌曲数
+pub const SONG_DETAIL_BATCH: usize = 1000;
+
+// 为缺少歌手和专辑信息的歌曲请求 /song/detail 并补全，按 SONG_DETAIL_BATCH 分批。
+// 接口没有返回的歌曲（例如已下架）保持原样，返回补全的歌曲数
+pub async fn enrich_songs<A: MusicApi + ?Sized>(api: &A, songs: &mut [Song]) -> Result<usize> {
+    let mut ids: Vec<i64> = songs.iter().filter(|s| s.needs_details()).map(|s| s.id).collect();
+    ids.sort();
+    ids.dedup();
+    let mut details = HashMap::new();
+    for batch in ids.chunks(SONG_DETAIL_BATCH) {
+        let response = api.get_song_detail(batch).await?;
+        if response.code != 200 {
+            return Err(anyhow::anyhow!("获取歌曲详情失败，状态码 {}", response.code));
+        }
+        details.extend(response.songs.into_iter().map(|song| (song.id, song)));
+    }
+
+    let mut enriched = 0;
+    for song in songs.iter_mut().filter(|s| s.needs_details()) {
+        if let Some(detail) = details.get(&song.id) {
+            // 接口返回的歌名为空时保留原来的
+            let name = std::mem::take(&mut song.name);
+            *song = detail.clone();
+            if song.name.is_empty() {
+                song.name = name;
+            }
+            enriched += 1;
+        }
+    }
+    tracing::debug!(requested = ids.len(), enriched, "歌曲详情补全完成");
+    Ok(enriched)
+}
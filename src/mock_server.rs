@@ -0,0 +1,169 @@
+use anyhow::Result;
+use axum::extract::{Query, State};
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// 分页接口中需要按 offset/limit 切片的列表字段
+const PAGED_KEYS: &[&str] = &["comments", "hotComments", "follow", "followeds", "playlist", "songs"];
+
+#[derive(Debug, Clone)]
+pub struct MockServerConfig {
+    // 存放接口响应的目录，/user/detail 对应 user_detail.json
+    pub fixtures_dir: PathBuf,
+    // 每个请求返回前的等待时间
+    pub latency: Duration,
+    // 每 N 个请求返回一次错误，None 表示不注入错误
+    pub fail_every: Option<u64>,
+    // 注入错误时返回的 code
+    pub error_code: i32,
+    // 每秒最多处理的请求数，超出时返回 405
+    pub rate_limit: Option<u32>,
+}
+
+impl MockServerConfig {
+    pub fn new(fixtures_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            fixtures_dir: fixtures_dir.into(),
+            latency: Duration::ZERO,
+            fail_every: None,
+            error_code: 502,
+            rate_limit: None,
+        }
+    }
+}
+
+struct ServerState {
+    config: MockServerConfig,
+    request_count: Mutex<u64>,
+    // 当前限流窗口的起始时间和已处理请求数
+    window: Mutex<(Instant, u32)>,
+    // 响应为数组的 fixture 按顺序返回，记录每个路径已返回到第几个
+    sequences: Mutex<HashMap<String, usize>>,
+}
+
+pub fn router(config: MockServerConfig) -> Router {
+    let state = Arc::new(ServerState {
+        config,
+        request_count: Mutex::new(0),
+        window: Mutex::new((Instant::now(), 0)),
+        sequences: Mutex::new(HashMap::new()),
+    });
+    Router::new().fallback(handle).with_state(state)
+}
+
+// 在指定地址启动模拟服务，返回实际监听的地址（端口传 0 时由系统分配）
+pub async fn spawn(config: MockServerConfig, addr: SocketAddr) -> Result<SocketAddr> {
+    let server = axum::Server::try_bind(&addr)?.serve(router(config).into_make_service());
+    let local_addr = server.local_addr();
+    tokio::spawn(server);
+    Ok(local_addr)
+}
+
+async fn handle(
+    State(state): State<Arc<ServerState>>,
+    uri: Uri,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let config = &state.config;
+    if !config.latency.is_zero() {
+        tokio::time::sleep(config.latency).await;
+    }
+
+    if let Some(limit) = config.rate_limit {
+        let mut window = state.window.lock().unwrap();
+        if window.0.elapsed() >= Duration::from_secs(1) {
+            *window = (Instant::now(), 0);
+        }
+        window.1 += 1;
+        if window.1 > limit {
+            return json_response(json!({ "code": 405, "message": "操作频繁，请稍候再试" }));
+        }
+    }
+
+    let count = {
+        let mut count = state.request_count.lock().unwrap();
+        *count += 1;
+        *count
+    };
+    if let Some(every) = config.fail_every {
+        if every > 0 && count % every == 0 {
+            return json_response(json!({ "code": config.error_code, "message": "模拟错误" }));
+        }
+    }
+
+    let path = uri.path();
+    let mut file = config
+        .fixtures_dir
+        .join(format!("{}.json", path.trim_matches('/').replace('/', "_")));
+    // 各类资源的评论结构相同，没有单独的模拟数据时使用歌曲评论
+    if !file.exists() && path.starts_with("/comment/") && path != "/comment/hot" {
+        file = config.fixtures_dir.join("comment_music.json");
+    }
+    let fixture: Value = match std::fs::read_to_string(&file)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+    {
+        Some(value) => value,
+        None => {
+            return (
+                StatusCode::NOT_FOUND,
+                json_response(json!({ "code": 404, "message": format!("没有 {} 的模拟数据", path) })),
+            )
+                .into_response()
+        }
+    };
+
+    let body = match fixture {
+        Value::Array(items) if !items.is_empty() => {
+            let mut sequences = state.sequences.lock().unwrap();
+            let index = sequences.entry(path.to_string()).or_insert(0);
+            let item = items[(*index).min(items.len() - 1)].clone();
+            *index += 1;
+            item
+        }
+        other => paginate(other, &query),
+    };
+    json_response(body)
+}
+
+// 按 offset/limit/before 对列表字段切片，并设置 more
+fn paginate(mut body: Value, query: &HashMap<String, String>) -> Value {
+    let offset = query.get("offset").and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
+    let limit = query.get("limit").and_then(|v| v.parse::<usize>().ok());
+    let before = query.get("before").and_then(|v| v.parse::<i64>().ok());
+
+    let Some(object) = body.as_object_mut() else {
+        return body;
+    };
+    let mut more = None;
+    for key in PAGED_KEYS {
+        let Some(Value::Array(items)) = object.get_mut(*key) else {
+            continue;
+        };
+        let mut list: Vec<Value> = std::mem::take(items);
+        if let Some(before) = before {
+            list.retain(|item| item.get("time").and_then(Value::as_i64).is_some_and(|t| t < before));
+        }
+        let start = if before.is_some() { 0 } else { offset.min(list.len()) };
+        let end = limit.map_or(list.len(), |limit| (start + limit).min(list.len()));
+        more = Some(more.unwrap_or(false) || end < list.len());
+        *items = list[start..end].to_vec();
+    }
+    if let Some(more) = more {
+        // /comment/hot 用 hasMore 表示还有下一页
+        let key = if object.contains_key("hasMore") { "hasMore" } else { "more" };
+        object.insert(key.to_string(), Value::Bool(more));
+    }
+    body
+}
+
+fn json_response(body: Value) -> Response {
+    axum::Json(body).into_response()
+}
@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tokio::sync::{mpsc, oneshot};
+
+// 先写入同目录下的 .part 临时文件，commit 时再改名为目标文件，
+// 中途出错或未 commit 就丢弃时删除临时文件，目标文件要么是旧内容要么是完整的新内容
+pub struct AtomicFile {
+    path: PathBuf,
+    temp: PathBuf,
+    writer: Option<BufWriter<File>>,
+    committed: bool,
+}
+
+impl AtomicFile {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut name = path.file_name().context("输出路径缺少文件名")?.to_os_string();
+        name.push(".part");
+        let temp = path.with_file_name(name);
+        let file = File::create(&temp).with_context(|| format!("无法创建 {}", temp.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp,
+            writer: Some(BufWriter::new(file)),
+            committed: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 落盘并替换目标文件
+    pub fn commit(mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            let file = writer.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+        }
+        fs::rename(&self.temp, &self.path)
+            .with_context(|| format!("无法写入 {}", self.path.display()))?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.as_mut().expect("文件已提交").write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.as_mut().expect("文件已提交").flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.writer.take();
+            let _ = fs::remove_file(&self.temp);
+        }
+    }
+}
+
+// 一次写入整个文件，同样经过临时文件改名
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut file = AtomicFile::create(path)?;
+    file.write_all(contents.as_ref())?;
+    file.commit()
+}
+
+// 逐个写入元素的 JSON 数组文件，内存中只保留写缓冲
+pub struct JsonArrayWriter {
+    file: AtomicFile,
+    len: usize,
+}
+
+impl JsonArrayWriter {
+    pub fn create(path: &Path) -> Result<Self> {
+        let mut file = AtomicFile::create(path)?;
+        file.write_all(b"[")?;
+        Ok(Self { file, len: 0 })
+    }
+
+    pub fn push<T: Serialize>(&mut self, item: &T) -> Result<()> {
+        self.file.write_all(if self.len == 0 { b"\n  " } else { b",\n  " })?;
+        serde_json::to_writer(&mut self.file, item)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.file.write_all(if self.len == 0 { b"]\n" } else { b"\n]\n" })?;
+        self.file.commit()
+    }
+}
+
+// 在单独的线程里运行同步的写入函数，经由有界通道接收数据。
+// 通道满时发送方等待，写入跟不上抓取时内存不会无限增长。
+// 写入线程存活时间与整次抓取相同，所以不占用 tokio 的 blocking 线程池
+pub struct BoundedWriter<T> {
+    sender: mpsc::Sender<T>,
+    done: oneshot::Receiver<Result<()>>,
+}
+
+impl<T: Send + 'static> BoundedWriter<T> {
+    // capacity 为通道中最多缓存的条数。写入线程持有 state，
+    // 所有发送句柄都释放后调用 finish 收尾，写入出错时不再调用 finish
+    pub fn spawn<S, W, F>(capacity: usize, mut state: S, mut write: W, finish: F) -> Self
+    where
+        S: Send + 'static,
+        W: FnMut(&mut S, T) -> Result<()> + Send + 'static,
+        F: FnOnce(S) -> Result<()> + Send + 'static,
+    {
+        let (sender, mut receiver) = mpsc::channel(capacity);
+        let (done_sender, done) = oneshot::channel();
+        std::thread::spawn(move || {
+            let result = (|| {
+                while let Some(item) = receiver.blocking_recv() {
+                    write(&mut state, item)?;
+                }
+                finish(state)
+            })();
+            let _ = done_sender.send(result);
+        });
+        Self { sender, done }
+    }
+
+    pub fn sender(&self) -> WriterHandle<T> {
+        WriterHandle(self.sender.clone())
+    }
+
+    // 关闭通道，等待剩余数据写完并返回写入过程中的错误。
+    // 调用前需释放 sender 复制出的句柄，否则会一直等待
+    pub async fn finish(self) -> Result<()> {
+        drop(self.sender);
+        self.done.await.context("写入线程异常退出")?
+    }
+}
+
+// 发送到 BoundedWriter 的句柄，可以在多个任务间复制
+pub struct WriterHandle<T>(mpsc::Sender<T>);
+
+impl<T> Clone for WriterHandle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> WriterHandle<T> {
+    pub async fn send(&self, item: T) -> Result<()> {
+        self.0
+            .send(item)
+            .await
+            .map_err(|_| anyhow::anyhow!("写入线程已停止"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncommitted_files_leave_target_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        fs::write(&path, "old").unwrap();
+
+        let mut file = AtomicFile::create(&path).unwrap();
+        file.write_all(b"new").unwrap();
+        drop(file);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let mut writer = JsonArrayWriter::create(&path).unwrap();
+        writer.push(&1).unwrap();
+        writer.push(&serde_json::json!({ "a": 2 })).unwrap();
+        writer.finish().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value, serde_json::json!([1, { "a": 2 }]));
+    }
+
+    #[tokio::test]
+    async fn bounded_writer_reports_write_errors() {
+        let writer = BoundedWriter::spawn(
+            1,
+            (),
+            |_, n: u32| if n < 3 { Ok(()) } else { Err(anyhow::anyhow!("磁盘已满")) },
+            |_| Ok(()),
+        );
+        let handle = writer.sender();
+        let mut failed = false;
+        for n in 0..10 {
+            if handle.send(n).await.is_err() {
+                failed = true;
+                break;
+            }
+        }
+        assert!(failed);
+        drop(handle);
+        assert!(writer.finish().await.unwrap_err().to_string().contains("磁盘已满"));
+    }
+}
@@ -0,0 +1,215 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use crate::report::SongReport;
+
+// 进度条模式下的刷新间隔
+const BARS_REFRESH: Duration = Duration::from_millis(200);
+// JSON 模式下两次进度输出的间隔
+const JSON_INTERVAL: Duration = Duration::from_secs(5);
+
+// 进度输出方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum ProgressMode {
+    // 终端进度条
+    #[default]
+    Bars,
+    // 定期向 stderr 输出一行 JSON，适合非终端环境
+    Json,
+    // 不输出进度
+    Quiet,
+}
+
+// 一次批量抓取的进度。单曲进度条的长度是该歌曲的评论总数，
+// 总进度条按所有歌曲的评论总数估算，同时显示的单曲进度条不超过 max_visible 个
+pub struct CrawlProgress {
+    mode: ProgressMode,
+    multi: MultiProgress,
+    overall: ProgressBar,
+    songs: Vec<ProgressBar>,
+    // 单曲进度条是否已显示在终端上
+    shown: Vec<AtomicBool>,
+    visible: AtomicUsize,
+    max_visible: usize,
+    finished: AtomicUsize,
+    started: Instant,
+}
+
+impl CrawlProgress {
+    // 创建进度并启动后台刷新，song_count 为本次要抓取的歌曲数
+    pub fn new(song_count: usize, mode: ProgressMode, max_visible: usize) -> Arc<Self> {
+        let multi = match mode {
+            ProgressMode::Bars => MultiProgress::new(),
+            _ => MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+        };
+        let overall = multi.add(ProgressBar::no_length());
+        overall.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} 条评论 {msg} 剩余 {eta}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("##-"),
+        );
+        let song_style = ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {prefix:.green} {bar:40.yellow/red} {pos}/{len} 条评论")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("##-");
+        let songs = (0..song_count)
+            .map(|_| {
+                let bar = ProgressBar::hidden();
+                bar.set_style(song_style.clone());
+                bar
+            })
+            .collect();
+
+        let progress = Arc::new(Self {
+            mode,
+            multi,
+            overall,
+            songs,
+            shown: (0..song_count).map(|_| AtomicBool::new(false)).collect(),
+            visible: AtomicUsize::new(0),
+            max_visible,
+            finished: AtomicUsize::new(0),
+            started: Instant::now(),
+        });
+        progress.refresh();
+        if mode != ProgressMode::Quiet {
+            tokio::spawn(tick(Arc::downgrade(&progress)));
+        }
+        progress
+    }
+
+    // 第 index 首歌的进度条，扫描时据此设置长度并逐条前进
+    pub fn song_bar(&self, index: usize) -> ProgressBar {
+        self.songs[index].clone()
+    }
+
+    // 歌曲开始抓取时调用，显示的进度条未达上限才会显示它
+    pub fn start_song(&self, index: usize, label: String) {
+        let bar = &self.songs[index];
+        bar.set_prefix(label);
+        if self.mode != ProgressMode::Bars {
+            return;
+        }
+        let slot = self
+            .visible
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| (v < self.max_visible).then_some(v + 1));
+        if slot.is_ok() {
+            self.shown[index].store(true, Ordering::Relaxed);
+            self.multi.add(bar.clone());
+        }
+    }
+
+    // 歌曲抓取结束时调用，以实际读取的条数作为这首歌的长度
+    pub fn finish_song(&self, index: usize, report: &SongReport) {
+        let bar = &self.songs[index];
+        bar.set_length(bar.position());
+        if self.shown[index].swap(false, Ordering::Relaxed) {
+            bar.finish_and_clear();
+            self.multi.remove(bar);
+            self.visible.fetch_sub(1, Ordering::Relaxed);
+        }
+        self.finished.fetch_add(1, Ordering::Relaxed);
+        if self.mode == ProgressMode::Json {
+            eprintln!("{}", json!({ "event": "song_finished", "song": report }));
+        }
+        self.refresh();
+    }
+
+    // 全部歌曲结束后调用
+    pub fn finish(&self) {
+        self.refresh();
+        self.overall.finish_with_message("所有歌曲评论获取完成！");
+        if self.mode == ProgressMode::Json {
+            let mut event = self.snapshot();
+            event["event"] = json!("finished");
+            eprintln!("{}", event);
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.finished.load(Ordering::Relaxed) >= self.songs.len()
+    }
+
+    fn scanned(&self) -> u64 {
+        self.songs.iter().map(|bar| bar.position()).sum()
+    }
+
+    // 用各首歌的进度更新总进度条
+    fn refresh(&self) {
+        if let Some(total) = estimate_total(self.songs.iter().map(|bar| bar.length())) {
+            self.overall.set_length(total);
+        }
+        self.overall.set_position(self.scanned());
+        self.overall.set_message(format!(
+            "歌曲 {}/{}",
+            self.finished.load(Ordering::Relaxed),
+            self.songs.len()
+        ));
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        let estimated_total = self.overall.length();
+        json!({
+            "event": "progress",
+            "songs_finished": self.finished.load(Ordering::Relaxed),
+            "songs_total": self.songs.len(),
+            "scanned": self.overall.position(),
+            "estimated_total": estimated_total,
+            "elapsed_secs": self.started.elapsed().as_secs_f64(),
+            "eta_secs": estimated_total.map(|_| self.overall.eta().as_secs()),
+        })
+    }
+}
+
+// 后台定期刷新总进度，JSON 模式下同时输出一行进度
+async fn tick(progress: Weak<CrawlProgress>) {
+    let interval = match progress.upgrade().map(|p| p.mode) {
+        Some(ProgressMode::Json) => JSON_INTERVAL,
+        _ => BARS_REFRESH,
+    };
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(progress) = progress.upgrade() else {
+            return;
+        };
+        if progress.done() {
+            return;
+        }
+        progress.refresh();
+        if progress.mode == ProgressMode::Json {
+            eprintln!("{}", progress.snapshot());
+        }
+    }
+}
+
+// 估算所有歌曲的评论总数：还不知道总数的歌曲按已知歌曲的平均值计，一首都不知道时返回 None
+fn estimate_total(lengths: impl Iterator<Item = Option<u64>>) -> Option<u64> {
+    let (mut known, mut sum, mut unknown) = (0u64, 0u64, 0u64);
+    for length in lengths {
+        match length {
+            Some(length) => {
+                known += 1;
+                sum += length;
+            }
+            None => unknown += 1,
+        }
+    }
+    (known > 0).then(|| sum + unknown * sum / known)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_songs_are_estimated_from_known_average() {
+        assert_eq!(estimate_total([None, None].into_iter()), None);
+        assert_eq!(estimate_total([Some(100), Some(300)].into_iter()), Some(400));
+        assert_eq!(estimate_total([Some(100), None, Some(300), None].into_iter()), Some(800));
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::transport::{HttpConfig, HttpRequest, HttpResponse, ReqwestTransport, Transport};
+
+// 网易云接口表示请求过于频繁的 code
+const RATE_LIMIT_CODES: &[i64] = &[405, -460];
+
+struct PoolMember {
+    proxy: String,
+    transport: Arc<dyn Transport>,
+    // 被暂时剔除时记录恢复时间
+    ejected_until: Mutex<Option<Instant>>,
+}
+
+impl PoolMember {
+    fn available_at(&self) -> Option<Instant> {
+        *self.ejected_until.lock().unwrap()
+    }
+
+    fn is_available(&self, now: Instant) -> bool {
+        self.available_at().is_none_or(|until| until <= now)
+    }
+}
+
+// 在多个代理之间轮询的传输层。遇到网络错误或被限流的代理会被剔除一段时间，
+// 请求改由下一个代理重试
+pub struct ProxyPoolTransport {
+    members: Vec<PoolMember>,
+    next: AtomicUsize,
+    eject_for: Duration,
+}
+
+impl ProxyPoolTransport {
+    pub fn new(proxies: &[String], config: &HttpConfig, eject_for: Duration) -> Result<Self> {
+        let transports = proxies
+            .iter()
+            .map(|proxy| {
+                let config = HttpConfig {
+                    proxy: Some(proxy.clone()),
+                    ..config.clone()
+                };
+                Ok((proxy.clone(), Arc::new(ReqwestTransport::with_config(&config)?) as Arc<dyn Transport>))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::from_transports(transports, eject_for))
+    }
+
+    // 使用任意传输层作为池成员，便于测试
+    pub fn from_transports(transports: Vec<(String, Arc<dyn Transport>)>, eject_for: Duration) -> Self {
+        Self {
+            members: transports
+                .into_iter()
+                .map(|(proxy, transport)| PoolMember {
+                    proxy,
+                    transport,
+                    ejected_until: Mutex::new(None),
+                })
+                .collect(),
+            next: AtomicUsize::new(0),
+            eject_for,
+        }
+    }
+
+    // 当前可用的代理数
+    pub fn available(&self) -> usize {
+        let now = Instant::now();
+        self.members.iter().filter(|m| m.is_available(now)).count()
+    }
+
+    // 按轮询顺序选择下一个可用代理；全部被剔除时选择最早恢复的那个
+    fn pick(&self) -> Option<&PoolMember> {
+        if self.members.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..self.members.len())
+            .map(|i| &self.members[(start + i) % self.members.len()])
+            .find(|m| m.is_available(now))
+            .or_else(|| self.members.iter().min_by_key(|m| m.available_at()))
+    }
+
+    fn eject(&self, member: &PoolMember, reason: &str) {
+        tracing::warn!(proxy = %member.proxy, eject_secs = self.eject_for.as_secs(), reason, "代理暂停使用");
+        *member.ejected_until.lock().unwrap() = Some(Instant::now() + self.eject_for);
+    }
+}
+
+#[async_trait]
+impl Transport for ProxyPoolTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let mut last = None;
+        for _ in 0..self.members.len() {
+            let Some(member) = self.pick() else { break };
+            match member.transport.send(request.clone()).await {
+                Ok(response) if is_rate_limited(&response) => {
+                    self.eject(member, "请求过于频繁");
+                    last = Some(Ok(response));
+                }
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    self.eject(member, &e.to_string());
+                    last = Some(Err(e));
+                }
+            }
+        }
+        last.unwrap_or_else(|| Err(anyhow::anyhow!("代理池为空")))
+    }
+}
+
+fn is_rate_limited(response: &HttpResponse) -> bool {
+    if response.status == 429 {
+        return true;
+    }
+    serde_json::from_slice::<serde_json::Value>(&response.body)
+        .ok()
+        .and_then(|body| body.get("code").and_then(|c| c.as_i64()))
+        .is_some_and(|code| RATE_LIMIT_CODES.contains(&code))
+}
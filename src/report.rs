@@ -0,0 +1,148 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::output;
+use crate::types::ResourceType;
+
+// 单首歌曲（或其他资源的评论区）的抓取结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SongReport {
+    // 评论所属的资源类型，歌曲时省略
+    #[serde(default, skip_serializing_if = "ResourceType::is_song")]
+    pub resource: ResourceType,
+    // 资源 ID，资源为歌曲时即歌曲 ID
+    pub song_id: i64,
+    pub name: String,
+    pub target_uids: Vec<i64>,
+    // 已读取的评论数
+    pub scanned: usize,
+    pub pages: usize,
+    // 发出的请求数，包括重试
+    pub requests: usize,
+    pub matched: usize,
+    // 每个目标用户匹配到的评论数
+    pub matched_by_user: BTreeMap<i64, usize>,
+    // 每个目标用户被其他人提及（@ 或回复）的评论数，没有查找提及时为空
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mentions_by_user: BTreeMap<i64, usize>,
+    // 在热门评论中找到了所有目标用户，没有翻页
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hot_only: bool,
+    // 失败原因，成功时为 None
+    pub error: Option<String>,
+}
+
+// 一次评论抓取的汇总报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlReport {
+    pub target_uids: Vec<i64>,
+    // 开始时间（RFC 3339）
+    pub started_at: String,
+    pub elapsed_secs: f64,
+    pub songs: Vec<SongReport>,
+}
+
+impl CrawlReport {
+    pub fn failed(&self) -> impl Iterator<Item = &SongReport> {
+        self.songs.iter().filter(|s| s.error.is_some())
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.failed().next().is_some()
+    }
+
+    pub fn total_pages(&self) -> usize {
+        self.songs.iter().map(|s| s.pages).sum()
+    }
+
+    pub fn total_requests(&self) -> usize {
+        self.songs.iter().map(|s| s.requests).sum()
+    }
+
+    pub fn total_scanned(&self) -> usize {
+        self.songs.iter().map(|s| s.scanned).sum()
+    }
+
+    pub fn total_matched(&self) -> usize {
+        self.songs.iter().map(|s| s.matched).sum()
+    }
+
+    // 各目标用户在所有歌曲下匹配到的评论数
+    pub fn matched_by_user(&self) -> BTreeMap<i64, usize> {
+        let mut totals: BTreeMap<i64, usize> = self.target_uids.iter().map(|&uid| (uid, 0)).collect();
+        for song in &self.songs {
+            for (&uid, &count) in &song.matched_by_user {
+                *totals.entry(uid).or_default() += count;
+            }
+        }
+        totals
+    }
+
+    // 各目标用户在所有歌曲下被提及的评论数
+    pub fn mentions_by_user(&self) -> BTreeMap<i64, usize> {
+        let mut totals = BTreeMap::new();
+        for song in &self.songs {
+            for (&uid, &count) in &song.mentions_by_user {
+                *totals.entry(uid).or_default() += count;
+            }
+        }
+        totals
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        output::write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for CrawlReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed: Vec<_> = self.failed().collect();
+        writeln!(f, "抓取完成，用时 {:.1} 秒", self.elapsed_secs)?;
+        // 全部是歌曲时沿用原来的说法，否则按评论区计数
+        if self.songs.iter().all(|s| s.resource.is_song()) {
+            write!(f, "歌曲 {} 首（失败 {} 首）", self.songs.len(), failed.len())?;
+        } else {
+            write!(f, "评论区 {} 个（失败 {} 个）", self.songs.len(), failed.len())?;
+        }
+        writeln!(f, "，评论页 {} 页，请求 {} 次", self.total_pages(), self.total_requests())?;
+        if self.target_uids.is_empty() {
+            write!(f, "读取评论 {} 条，导出 {} 条", self.total_scanned(), self.total_matched())?;
+        } else {
+            write!(f, "读取评论 {} 条，匹配到 {} 条", self.total_scanned(), self.total_matched())?;
+        }
+        let mentions = self.mentions_by_user();
+        if !mentions.is_empty() {
+            write!(f, "，提及目标用户的评论 {} 条", mentions.values().sum::<usize>())?;
+        }
+        if self.target_uids.len() > 1 {
+            for (uid, count) in self.matched_by_user() {
+                write!(f, "\n  用户 {}: {} 条", uid, count)?;
+                if let Some(mentioned) = mentions.get(&uid) {
+                    write!(f, "，被提及 {} 条", mentioned)?;
+                }
+            }
+        }
+        let hot_only = self.songs.iter().filter(|s| s.hot_only).count();
+        if hot_only > 0 {
+            write!(f, "\n其中 {} 首在热门评论中找到了所有目标用户，未翻页", hot_only)?;
+        }
+        for song in failed {
+            f.write_str("\n  失败: ")?;
+            if !song.resource.is_song() {
+                write!(f, "{} ", song.resource.label())?;
+            }
+            write!(
+                f,
+                "{} ({}) 已读取 {} 条: {}",
+                song.name,
+                song.song_id,
+                song.scanned,
+                song.error.as_deref().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::comment_stream::PageCursor;
+use crate::filter::Filter;
+use crate::output;
+use crate::types::ResourceType;
+
+// 默认的重试队列文件
+pub const RETRY_QUEUE_PATH: &str = "comments/retry_queue.json";
+
+// 抓取失败的歌曲（或其他资源的评论区），记录失败时的位置以便从这里继续
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryEntry {
+    // 评论所属的资源类型，歌曲时省略
+    #[serde(default, skip_serializing_if = "ResourceType::is_song")]
+    pub resource: ResourceType,
+    // 资源 ID，资源为歌曲时即歌曲 ID
+    pub song_id: i64,
+    pub name: String,
+    // 这首歌要匹配的目标用户，已排序
+    pub target_uids: Vec<i64>,
+    // 抓取时使用的筛选条件，重试时沿用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
+    // 是否同时查找提及目标用户的评论
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mentions: bool,
+    // 失败页的分页位置
+    pub cursor: PageCursor,
+    // 失败前已读取的评论数（包括之前各次重试）
+    pub scanned: usize,
+    pub error: String,
+    // 已失败的次数
+    pub attempts: u32,
+    // 最近一次失败的时间（RFC 3339）
+    pub failed_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryQueue {
+    pub entries: Vec<RetryEntry>,
+}
+
+impl RetryQueue {
+    // 文件不存在时返回空队列
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(&fs::read_to_string(path)?)
+            .with_context(|| format!("无法解析重试队列 {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        output::write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // 加入或更新一首歌，同一组目标用户的同一首歌只保留一条并累计失败次数
+    pub fn upsert(&mut self, mut entry: RetryEntry) {
+        match self.entries.iter_mut().find(|e| {
+            e.resource == entry.resource && e.song_id == entry.song_id && e.target_uids == entry.target_uids
+        }) {
+            Some(existing) => {
+                entry.attempts += existing.attempts;
+                *existing = entry;
+            }
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn remove(&mut self, resource: ResourceType, song_id: i64, target_uids: &[i64]) {
+        self.entries
+            .retain(|e| !(e.resource == resource && e.song_id == song_id && e.target_uids == target_uids));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(song_id: i64, offset: i32) -> RetryEntry {
+        RetryEntry {
+            resource: ResourceType::Song,
+            song_id,
+            name: format!("song{}", song_id),
+            target_uids: vec![7],
+            filter: None,
+            mentions: false,
+            cursor: PageCursor::Offset(offset),
+            scanned: offset as usize,
+            error: "接口返回状态码 405".to_string(),
+            attempts: 1,
+            failed_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn upsert_replaces_entry_and_accumulates_attempts() {
+        let mut queue = RetryQueue::default();
+        queue.upsert(entry(1, 100));
+        queue.upsert(entry(2, 0));
+        queue.upsert(entry(1, 300));
+        // 同一 ID 的专辑是另一个评论区
+        queue.upsert(RetryEntry {
+            resource: ResourceType::Album,
+            ..entry(1, 0)
+        });
+        assert_eq!(queue.entries.len(), 3);
+        assert_eq!(queue.entries[0].cursor, PageCursor::Offset(300));
+        assert_eq!(queue.entries[0].attempts, 2);
+
+        queue.remove(ResourceType::Song, 1, &[7]);
+        assert_eq!(queue.entries.len(), 2);
+        assert_eq!(queue.entries[0].song_id, 2);
+        assert_eq!(queue.entries[1].resource, ResourceType::Album);
+    }
+
+    #[test]
+    fn cursor_and_filter_round_trip_through_json() {
+        let json = serde_json::to_value(entry(1, 200)).unwrap();
+        assert_eq!(json["cursor"], serde_json::json!({ "offset": 200 }));
+        assert!(json.get("filter").is_none());
+        assert!(json.get("resource").is_none());
+        let back: RetryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.cursor, PageCursor::Offset(200));
+        assert!(back.filter.is_none());
+
+        let mut filtered = entry(1, 200);
+        filtered.filter = Some(Filter::parse("liked>=10 && content~/晚安/").unwrap());
+        let json = serde_json::to_value(filtered).unwrap();
+        assert_eq!(json["filter"], "liked>=10 && content~/晚安/");
+        let back: RetryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.filter.unwrap().to_string(), "liked>=10 && content~/晚安/");
+
+        let event = RetryEntry {
+            resource: ResourceType::Event { owner: 32953014 },
+            ..entry(1, 0)
+        };
+        let json = serde_json::to_value(event).unwrap();
+        assert_eq!(json["resource"], "event:32953014");
+        let back: RetryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(back.resource, ResourceType::Event { owner: 32953014 });
+    }
+}
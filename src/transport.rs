@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::API_BASE_URL;
+
+// 发往 NeteaseCloudMusicApi 的一次 GET 请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    // 接口路径，例如 /comment/music
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub cookie: Option<String>,
+}
+
+impl HttpRequest {
+    // 路径加排序后的查询参数，skip 中的参数不参与计算
+    pub fn key(&self, skip: &[&str]) -> String {
+        request_key(&self.path, &self.query, skip)
+    }
+
+    pub fn query_value(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub(crate) fn request_key(path: &str, query: &[(String, String)], skip: &[&str]) -> String {
+    let mut params: Vec<String> = query
+        .iter()
+        .filter(|(k, _)| !skip.contains(&k.as_str()))
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    params.sort();
+    format!("{}?{}", path, params.join("&"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    // 响应中的 set-cookie 头
+    pub set_cookies: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn json(body: &serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            set_cookies: Vec::new(),
+            body: body.to_string().into_bytes(),
+        }
+    }
+}
+
+// HTTP 传输层，NeteaseMusicClient 的所有请求都经由它发出
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+// ReqwestTransport 的连接配置
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub base_url: String,
+    // 代理地址，支持 http://、https:// 和 socks5://
+    pub proxy: Option<String>,
+    // 建立连接的超时时间
+    pub connect_timeout: Option<Duration>,
+    // 单个请求的总时限，从发出请求算到读完响应为止，不是读取空闲超时：
+    // 响应持续有数据但总耗时超过它同样会失败，下载大响应时需要相应调大
+    pub request_timeout: Option<Duration>,
+    pub user_agent: Option<String>,
+    // 附加到每个请求上的请求头
+    pub headers: Vec<(String, String)>,
+    // 海外服务器需要的 realIP 参数，会附加到每个请求的查询参数中
+    pub real_ip: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            base_url: API_BASE_URL.to_string(),
+            proxy: None,
+            connect_timeout: Some(Duration::from_secs(10)),
+            request_timeout: Some(Duration::from_secs(30)),
+            user_agent: None,
+            headers: Vec::new(),
+            real_ip: None,
+        }
+    }
+}
+
+// 基于 reqwest 的默认实现
+#[derive(Clone)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    base_url: String,
+    real_ip: Option<String>,
+}
+
+impl ReqwestTransport {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let config = HttpConfig {
+            base_url: base_url.into(),
+            ..HttpConfig::default()
+        };
+        Self::with_config(&config).expect("默认 HTTP 配置无效")
+    }
+
+    pub fn with_config(config: &HttpConfig) -> Result<Self> {
+        Ok(Self {
+            client: build_client(config)?,
+            base_url: config.base_url.clone(),
+            real_ip: config.real_ip.clone(),
+        })
+    }
+}
+
+// 按配置创建 reqwest 客户端，base_url 和 real_ip 由调用方处理
+pub(crate) fn build_client(config: &HttpConfig) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(
+            reqwest::Proxy::all(proxy).with_context(|| format!("无效的代理地址 {}", proxy))?,
+        );
+    }
+    if let Some(timeout) = config.connect_timeout {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = config.request_timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(user_agent) = &config.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (name, value) in &config.headers {
+        headers.insert(
+            reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("无效的请求头名称 {}", name))?,
+            reqwest::header::HeaderValue::from_str(value)
+                .with_context(|| format!("无效的请求头 {}: {}", name, value))?,
+        );
+    }
+    Ok(builder.default_headers(headers).build()?)
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self::new(API_BASE_URL)
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let url = format!("{}{}", self.base_url, request.path);
+        let mut builder = self.client.get(&url).query(&request.query);
+        if let Some(ip) = &self.real_ip {
+            if request.query_value("realIP").is_none() {
+                builder = builder.query(&[("realIP", ip)]);
+            }
+        }
+        if let Some(cookie) = &request.cookie {
+            builder = builder.header("Cookie", cookie);
+        }
+        let response = builder
+            .send()
+            .await
+            .with_context(|| format!("请求 {} 失败", request.path))?;
+        read_response(response).await
+    }
+}
+
+pub(crate) async fn read_response(response: reqwest::Response) -> Result<HttpResponse> {
+    let status = response.status().as_u16();
+    let set_cookies = response
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .collect();
+    let body = response.bytes().await?.to_vec();
+
+    Ok(HttpResponse {
+        status,
+        set_cookies,
+        body,
+    })
+}
+
+// 按接口路径返回预设响应的传输层，用于离线测试
+#[derive(Default)]
+pub struct MockTransport {
+    routes: Mutex<HashMap<String, VecDeque<HttpResponse>>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 为某个路径追加一个响应，同一路径的响应按追加顺序依次返回，
+    // 最后一个响应会被重复使用
+    pub fn push(&self, path: &str, response: HttpResponse) {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(path.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    pub fn push_json(&self, path: &str, body: serde_json::Value) {
+        self.push(path, HttpResponse::json(&body));
+    }
+
+    // 已收到的请求，按发送顺序排列
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn requests_to(&self, path: &str) -> Vec<HttpRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.path == path)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+        let path = request.path.clone();
+        self.requests.lock().unwrap().push(request);
+
+        let mut routes = self.routes.lock().unwrap();
+        let queue = routes
+            .get_mut(&path)
+            .ok_or_else(|| anyhow::anyhow!("没有为 {} 预设响应", path))?;
+        let response = if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        };
+        response.ok_or_else(|| anyhow::anyhow!("没有为 {} 预设响应", path))
+    }
+}
@@ -0,0 +1,755 @@
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub cookie: String,
+    #[serde(default)]
+    pub token: String,
+    pub account: Option<Account>,
+    pub profile: Option<Profile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Account {
+    pub id: i64,
+    #[serde(rename = "userName")]
+    pub user_name: String,
+    #[serde(rename = "type")]
+    pub account_type: i32,
+    pub status: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub code: i32,
+    pub profile: Profile,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub nickname: String,
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: String,
+    #[serde(rename = "backgroundUrl")]
+    pub background_url: Option<String>,
+    pub signature: Option<String>,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+    #[serde(rename = "userName")]
+    #[serde(default)]
+    pub user_name: String,
+    #[serde(rename = "accountType")]
+    #[serde(default)]
+    pub account_type: i32,
+    #[serde(rename = "vipType")]
+    #[serde(default)]
+    pub vip_type: i32,
+    #[serde(default)]
+    pub followed: bool,
+    #[serde(default)]
+    pub follows: i32,
+    #[serde(default)]
+    pub followeds: i32,
+    #[serde(rename = "eventCount")]
+    #[serde(default)]
+    pub event_count: i32,
+    #[serde(rename = "playlistCount")]
+    #[serde(default)]
+    pub playlist_count: i32,
+    #[serde(rename = "playlistBeSubscribedCount")]
+    #[serde(default)]
+    pub playlist_be_subscribed_count: i32,
+    #[serde(default)]
+    pub province: i32,
+    #[serde(default)]
+    pub city: i32,
+    #[serde(default)]
+    pub birthday: i64,
+    #[serde(default)]
+    pub gender: i32,
+    pub description: Option<String>,
+    #[serde(rename = "detailDescription")]
+    pub detail_description: Option<String>,
+    #[serde(rename = "defaultAvatar")]
+    #[serde(default)]
+    pub default_avatar: bool,
+    #[serde(rename = "expertTags")]
+    pub expert_tags: Option<Vec<String>>,
+    pub experts: Option<serde_json::Value>,
+    #[serde(rename = "djStatus")]
+    #[serde(default)]
+    pub dj_status: i32,
+    #[serde(default)]
+    pub mutual: bool,
+    #[serde(rename = "remarkName")]
+    pub remark_name: Option<String>,
+    #[serde(rename = "authStatus")]
+    #[serde(default)]
+    pub auth_status: i32,
+    #[serde(default)]
+    pub blacklist: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub code: i32,
+    #[serde(rename = "allData")]
+    pub all_data: Vec<SongData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SongData {
+    pub score: i64,
+    pub song: Song,
+}
+
+// 歌曲及其元数据，字段名与 /song/detail 一致。听歌榜单和歌单接口通常也会带上元数据，
+// 只有 ID 和歌名时可以用 metadata::enrich_songs 补全
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Song {
+    pub name: String,
+    pub id: i64,
+    #[serde(rename = "ar", default)]
+    pub artists: Vec<Artist>,
+    #[serde(rename = "al", default)]
+    pub album: Option<SongAlbum>,
+    // 时长（毫秒）
+    #[serde(rename = "dt", default)]
+    pub duration: i64,
+    // 发行时间（毫秒时间戳），未知时为 0
+    #[serde(rename = "publishTime", default)]
+    pub publish_time: i64,
+    // 别名，例如影视剧主题曲的说明
+    #[serde(rename = "alia", default)]
+    pub alias: Vec<String>,
+}
+
+impl Song {
+    pub fn new(id: i64, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            id,
+            artists: Vec::new(),
+            album: None,
+            duration: 0,
+            publish_time: 0,
+            alias: Vec::new(),
+        }
+    }
+
+    // 是否还缺少歌手和专辑信息
+    pub fn needs_details(&self) -> bool {
+        self.artists.is_empty() && self.album.is_none()
+    }
+
+    // 歌手名，多位歌手用 / 连接
+    pub fn artist_names(&self) -> String {
+        self.artists.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join("/")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artist {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+}
+
+// 歌曲所属的专辑
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SongAlbum {
+    pub id: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "picUrl", default)]
+    pub pic_url: Option<String>,
+}
+
+// /song/detail 返回的歌曲详情
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SongDetailResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub songs: Vec<Song>,
+}
+
+// /playlist/track/all 返回的歌单歌曲
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaylistTracksResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub songs: Vec<Song>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaylistResponse {
+    pub code: i32,
+    pub playlist: Vec<Playlist>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Playlist {
+    pub id: i64,
+    pub name: String,
+    #[serde(rename = "trackCount")]
+    pub track_count: i32,
+    #[serde(rename = "playCount")]
+    pub play_count: i64,
+    pub creator: Creator,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Creator {
+    pub nickname: String,
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Comment {
+    #[serde(rename = "commentId")]
+    pub comment_id: i64,
+    pub user: CommentUser,
+    pub content: String,
+    pub time: i64,
+    #[serde(rename = "likedCount")]
+    pub liked_count: i32,
+    // 这条评论回复的评论，不是回复时为空
+    #[serde(rename = "beReplied", default)]
+    pub be_replied: Vec<BeReplied>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommentUser {
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+    pub nickname: String,
+    #[serde(rename = "avatarUrl", default)]
+    pub avatar_url: String,
+}
+
+// 被回复的评论
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BeReplied {
+    pub user: CommentUser,
+    #[serde(rename = "beRepliedCommentId", default)]
+    pub comment_id: i64,
+    // 被删除的评论没有内容
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserComment {
+    pub song_id: i64,
+    pub song_name: String,
+    pub comment_id: i64,
+    pub content: String,
+    pub time: i64,
+    pub liked_count: i32,
+}
+
+// /user/follows 返回的关注列表
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FollowsResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub follow: Vec<Follow>,
+    #[serde(default)]
+    pub more: bool,
+}
+
+// /user/followeds 返回的粉丝列表
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FollowedsResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub followeds: Vec<Follow>,
+    #[serde(default)]
+    pub more: bool,
+    #[serde(default)]
+    pub lasttime: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Follow {
+    pub nickname: String,
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: String,
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub followeds: i32,
+    #[serde(default)]
+    pub mutual: bool,
+    #[serde(rename = "vipType")]
+    #[serde(default)]
+    pub vip_type: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    // 只在第一页返回
+    #[serde(rename = "hotComments", default)]
+    pub hot_comments: Vec<Comment>,
+    #[serde(default)]
+    pub total: i32,
+    pub more: Option<bool>,
+}
+
+// /comment/hot 返回的热门评论
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HotCommentsResponse {
+    pub code: i32,
+    #[serde(rename = "hotComments", default)]
+    pub hot_comments: Vec<Comment>,
+    #[serde(default)]
+    pub total: i32,
+    #[serde(rename = "hasMore", default)]
+    pub has_more: bool,
+}
+
+// 可以评论的资源类型。动态的评论区由动态 ID 和发布者的用户 ID 共同确定，所以 Event 带上发布者。
+// 文本形式为 song、playlist、album、mv、video、dj 和 event:<发布者 UID>，命令行参数和 JSON 中都使用这种形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ResourceType {
+    #[default]
+    Song,
+    Playlist,
+    Album,
+    Mv,
+    Video,
+    // 电台节目
+    Dj,
+    // 用户动态
+    Event { owner: i64 },
+}
+
+impl ResourceType {
+    const NAMES: [&'static str; 7] = ["song", "playlist", "album", "mv", "video", "dj", "event:<UID>"];
+
+    // /comment/hot 的 type 参数
+    pub fn code(&self) -> i32 {
+        match self {
+            ResourceType::Song => 0,
+            ResourceType::Mv => 1,
+            ResourceType::Playlist => 2,
+            ResourceType::Album => 3,
+            ResourceType::Dj => 4,
+            ResourceType::Video => 5,
+            ResourceType::Event { .. } => 6,
+        }
+    }
+
+    // type 参数对应的 threadId 前缀，动态的发布者不影响前缀
+    pub fn thread_prefix_of(code: i32) -> Option<&'static str> {
+        [
+            ResourceType::Song,
+            ResourceType::Playlist,
+            ResourceType::Album,
+            ResourceType::Mv,
+            ResourceType::Video,
+            ResourceType::Dj,
+            ResourceType::Event { owner: 0 },
+        ]
+        .into_iter()
+        .find(|resource| resource.code() == code)
+        .map(|resource| resource.thread_prefix())
+    }
+
+    // 官方接口中评论区 threadId 的前缀，后面接 query_id
+    pub fn thread_prefix(&self) -> &'static str {
+        match self {
+            ResourceType::Song => "R_SO_4_",
+            ResourceType::Playlist => "A_PL_0_",
+            ResourceType::Album => "R_AL_3_",
+            ResourceType::Mv => "R_MV_5_",
+            ResourceType::Video => "R_VI_62_",
+            ResourceType::Dj => "A_DJ_1_",
+            ResourceType::Event { .. } => "A_EV_2_",
+        }
+    }
+
+    // 接口参数中的资源 ID，动态为 <动态 ID>_<发布者 UID>
+    pub fn query_id(&self, id: i64) -> String {
+        match self {
+            ResourceType::Event { owner } => format!("{}_{}", id, owner),
+            _ => id.to_string(),
+        }
+    }
+
+    // 完整的评论区 threadId
+    pub fn thread_id(&self, id: i64) -> String {
+        format!("{}{}", self.thread_prefix(), self.query_id(id))
+    }
+
+    // 获取这类资源评论的接口
+    pub fn comment_path(&self) -> &'static str {
+        match self {
+            ResourceType::Song => "/comment/music",
+            ResourceType::Playlist => "/comment/playlist",
+            ResourceType::Album => "/comment/album",
+            ResourceType::Mv => "/comment/mv",
+            ResourceType::Video => "/comment/video",
+            ResourceType::Dj => "/comment/dj",
+            ResourceType::Event { .. } => "/comment/event",
+        }
+    }
+
+    // 类型名，也用作输出文件名的前缀，例如 album_32311.json
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceType::Song => "song",
+            ResourceType::Playlist => "playlist",
+            ResourceType::Album => "album",
+            ResourceType::Mv => "mv",
+            ResourceType::Video => "video",
+            ResourceType::Dj => "dj",
+            ResourceType::Event { .. } => "event",
+        }
+    }
+
+    pub fn is_song(&self) -> bool {
+        *self == ResourceType::Song
+    }
+
+    // 进度条和日志中显示的名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResourceType::Song => "歌曲",
+            ResourceType::Playlist => "歌单",
+            ResourceType::Album => "专辑",
+            ResourceType::Mv => "MV",
+            ResourceType::Video => "视频",
+            ResourceType::Dj => "电台节目",
+            ResourceType::Event { .. } => "动态",
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceType::Event { owner } => write!(f, "event:{}", owner),
+            other => f.write_str(other.as_str()),
+        }
+    }
+}
+
+impl std::str::FromStr for ResourceType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let resource = match s.trim().to_ascii_lowercase().as_str() {
+            "song" => ResourceType::Song,
+            "playlist" => ResourceType::Playlist,
+            "album" => ResourceType::Album,
+            "mv" => ResourceType::Mv,
+            "video" => ResourceType::Video,
+            "dj" => ResourceType::Dj,
+            "event" => anyhow::bail!("动态的评论区需要发布者的用户 ID，格式为 event:<UID>"),
+            other => match other.strip_prefix("event:") {
+                Some(owner) => ResourceType::Event {
+                    owner: owner.parse().map_err(|_| anyhow::anyhow!("无效的动态发布者 ID：{}", owner))?,
+                },
+                None => anyhow::bail!("未知的资源类型 {}，可选 {}", s, Self::NAMES.join("、")),
+            },
+        };
+        Ok(resource)
+    }
+}
+
+impl Serialize for ResourceType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResourceType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// 添加新的结构体用于二维码登录
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QrKeyResponse {
+    pub code: i32,
+    #[serde(rename = "data")]
+    pub key_data: QrKeyData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QrKeyData {
+    #[serde(rename = "unikey")]
+    pub key: String,
+    pub qrimg: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QrCreateResponse {
+    pub code: i32,
+    #[serde(rename = "data")]
+    pub data: QrCreateData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QrCreateData {
+    pub qrurl: String,
+    pub qrimg: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QrCheckResponse {
+    pub code: i32,
+    pub message: String,
+    pub cookie: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct CommentOutput {
+    pub 用户: UserInfo,
+    pub 被回复: Vec<String>,
+    pub 挂件数据: Option<serde_json::Value>,
+    pub 显示楼层评论: Option<serde_json::Value>,
+    pub 状态: i32,
+    pub 评论ID: i64,
+    pub 内容: String,
+    pub 富文本内容: Option<serde_json::Value>,
+    pub 内容资源: Option<serde_json::Value>,
+    pub 时间: i64,
+    pub 时间字符串: String,
+    pub 需要显示时间: bool,
+    pub 点赞数: i32,
+    pub 表情链接: Option<serde_json::Value>,
+    pub 评论位置类型: i32,
+    pub 父评论ID: i64,
+    pub 装饰: serde_json::Map<String, serde_json::Value>,
+    pub 回复标记: Option<serde_json::Value>,
+    pub 等级: Option<serde_json::Value>,
+    pub 用户业务等级: Option<serde_json::Value>,
+    pub IP位置: IpLocation,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct UserInfo {
+    pub 地理位置: Option<serde_json::Value>,
+    pub 直播信息: Option<serde_json::Value>,
+    pub 是否匿名: i32,
+    pub 头像详情: Option<serde_json::Value>,
+    pub 用户类型: i32,
+    pub 头像链接: String,
+    pub 是否关注: bool,
+    pub 是否互相关注: bool,
+    pub 备注名: Option<String>,
+    pub 社交用户ID: Option<serde_json::Value>,
+    pub 会员权益: VipInfo,
+    pub 昵称: String,
+    pub 认证状态: i32,
+    pub 专家标签: Option<serde_json::Value>,
+    pub 专家: Option<serde_json::Value>,
+    pub 会员类型: i32,
+    pub 通用身份: Option<serde_json::Value>,
+    pub 用户ID: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct VipInfo {
+    pub associator: Option<serde_json::Value>,
+    pub musicPackage: Option<serde_json::Value>,
+    pub redplus: Option<serde_json::Value>,
+    pub redVipAnnualCount: i32,
+    pub redVipLevel: i32,
+    pub relationType: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct IpLocation {
+    pub IP: Option<String>,
+    pub 地理位置: String,
+    pub 用户ID: Option<serde_json::Value>,
+}
+
+// 将接口返回的评论转换为输出格式
+impl From<Comment> for CommentOutput {
+    fn from(comment: Comment) -> Self {
+        let time = Local.timestamp_millis_opt(comment.time).unwrap();
+        CommentOutput {
+            用户: UserInfo {
+                地理位置: None,
+                直播信息: None,
+                是否匿名: 0,
+                头像详情: None,
+                用户类型: 0,
+                头像链接: comment.user.avatar_url,
+                是否关注: false,
+                是否互相关注: false,
+                备注名: None,
+                社交用户ID: None,
+                会员权益: VipInfo {
+                    associator: None,
+                    musicPackage: None,
+                    redplus: None,
+                    redVipAnnualCount: -1,
+                    redVipLevel: 0,
+                    relationType: 0,
+                },
+                昵称: comment.user.nickname,
+                认证状态: 0,
+                专家标签: None,
+                专家: None,
+                会员类型: 0,
+                通用身份: None,
+                用户ID: comment.user.user_id,
+            },
+            被回复: comment.be_replied.into_iter().map(|r| r.user.nickname).collect(),
+            挂件数据: None,
+            显示楼层评论: None,
+            状态: 0,
+            评论ID: comment.comment_id,
+            内容: comment.content,
+            富文本内容: None,
+            内容资源: None,
+            时间: comment.time,
+            时间字符串: time.format("%Y-%m-%d").to_string(),
+            需要显示时间: true,
+            点赞数: comment.liked_count,
+            表情链接: None,
+            评论位置类型: 0,
+            父评论ID: 0,
+            装饰: serde_json::Map::new(),
+            回复标记: None,
+            等级: None,
+            用户业务等级: None,
+            IP位置: IpLocation {
+                IP: None,
+                地理位置: String::new(),
+                用户ID: None,
+            },
+        }
+    }
+}
+
+// 写在评论文件旁边的歌曲信息，例如 comments/user_{uid}/song_{id}.info.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct SongInfoOutput {
+    pub 歌曲ID: i64,
+    pub 歌名: String,
+    pub 歌手: Vec<String>,
+    pub 专辑: Option<String>,
+    pub 专辑ID: Option<i64>,
+    pub 时长: i64,
+    pub 时长字符串: String,
+    pub 发行时间: i64,
+    pub 发行时间字符串: Option<String>,
+    pub 别名: Vec<String>,
+}
+
+impl From<&Song> for SongInfoOutput {
+    fn from(song: &Song) -> Self {
+        let seconds = song.duration / 1000;
+        SongInfoOutput {
+            歌曲ID: song.id,
+            歌名: song.name.clone(),
+            歌手: song.artists.iter().map(|a| a.name.clone()).collect(),
+            专辑: song.album.as_ref().map(|a| a.name.clone()),
+            专辑ID: song.album.as_ref().map(|a| a.id),
+            时长: song.duration,
+            时长字符串: format!("{:02}:{:02}", seconds / 60, seconds % 60),
+            发行时间: song.publish_time,
+            发行时间字符串: (song.publish_time > 0)
+                .then(|| Local.timestamp_millis_opt(song.publish_time).single())
+                .flatten()
+                .map(|time| time.format("%Y-%m-%d").to_string()),
+            别名: song.alias.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_user_follows() {
+        let resp: FollowsResponse =
+            serde_json::from_str(include_str!("../tests/fixtures/user_follows.json")).unwrap();
+        assert_eq!(resp.code, 200);
+        assert!(resp.more);
+        assert_eq!(resp.follow.len(), 2);
+        assert_eq!(resp.follow[0].user_id, 1372386);
+        assert_eq!(resp.follow[0].followeds, 5123401);
+        assert!(resp.follow[0].mutual);
+        assert_eq!(resp.follow[0].vip_type, 11);
+        assert_eq!(resp.follow[1].signature, None);
+    }
+
+    #[test]
+    fn parse_user_followeds() {
+        let resp: FollowedsResponse =
+            serde_json::from_str(include_str!("../tests/fixtures/user_followeds.json")).unwrap();
+        assert_eq!(resp.code, 200);
+        assert!(!resp.more);
+        assert_eq!(resp.lasttime, Some(1699950000000));
+        assert_eq!(resp.followeds.len(), 1);
+        assert_eq!(resp.followeds[0].nickname, "夜行的猫");
+        assert_eq!(resp.followeds[0].vip_type, 10);
+    }
+
+    #[test]
+    fn song_info_reads_detail_fields() {
+        let song: Song = serde_json::from_value(serde_json::json!({
+            "name": "晴天",
+            "id": 186016,
+            "ar": [{ "id": 6452, "name": "周杰伦" }],
+            "al": { "id": 18905, "name": "叶惠美", "picUrl": "https://p1.music.126.net/a.jpg" },
+            "dt": 269000,
+            "publishTime": 1059580800000i64,
+            "alia": []
+        }))
+        .unwrap();
+        assert!(!song.needs_details());
+        let info = SongInfoOutput::from(&song);
+        assert_eq!(info.歌手, vec!["周杰伦"]);
+        assert_eq!(info.专辑.as_deref(), Some("叶惠美"));
+        assert_eq!(info.时长字符串, "04:29");
+        assert!(info.发行时间字符串.unwrap().starts_with("2003-07-3"));
+
+        // 只有 ID 和歌名时其余字段为空
+        let bare = SongInfoOutput::from(&Song::new(1, "x"));
+        assert!(bare.歌手.is_empty() && bare.专辑.is_none() && bare.发行时间字符串.is_none());
+        assert_eq!(bare.时长字符串, "00:00");
+    }
+
+    #[test]
+    fn resource_types_round_trip_through_text() {
+        for text in ["song", "playlist", "album", "mv", "video", "dj", "event:32953014"] {
+            let resource: ResourceType = text.parse().unwrap();
+            assert_eq!(resource.to_string(), text);
+        }
+        let event: ResourceType = "event:32953014".parse().unwrap();
+        assert_eq!(event.thread_id(6559519868), "A_EV_2_6559519868_32953014");
+        assert_eq!(ResourceType::Mv.thread_id(5436712), "R_MV_5_5436712");
+        assert!("event".parse::<ResourceType>().is_err());
+        assert!("artist".parse::<ResourceType>().is_err());
+    }
+}
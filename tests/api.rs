@@ -0,0 +1,136 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use indicatif::ProgressBar;
+use netease_music_api::graph::{Direction, GraphCrawlOptions};
+use netease_music_api::{
+    Comment, CommentResponse, CommentUser, Follow, FollowedsResponse, FollowsResponse, GraphCrawler,
+    HotCommentsResponse, LoginResponse, MusicApi, PlaylistResponse, PlaylistTracksResponse, QrCheckResponse,
+    ResourceType, SongCrawler, SongDetailResponse, UserProfile, UserRecord,
+};
+
+// 不经过 HTTP 的内存数据源：用户 n 关注 n + 1，歌曲下有 150 条评论，其中用户 7 发了两条
+struct InMemoryApi;
+
+fn unsupported<T>() -> Result<T> {
+    Err(anyhow::anyhow!("内存数据源不支持该接口"))
+}
+
+// 响应类型可以直接构造，其他数据源实现 MusicApi 时不需要经过 JSON
+fn follow(uid: i64) -> Follow {
+    Follow {
+        nickname: format!("user{}", uid),
+        user_id: uid,
+        avatar_url: String::new(),
+        signature: None,
+        followeds: 0,
+        mutual: false,
+        vip_type: 0,
+    }
+}
+
+#[async_trait]
+impl MusicApi for InMemoryApi {
+    async fn login_cellphone(&mut self, _phone: &str, _password: &str) -> Result<LoginResponse> {
+        unsupported()
+    }
+
+    async fn get_qr_key(&self) -> Result<String> {
+        unsupported()
+    }
+
+    async fn check_qr(&self, _key: &str) -> Result<QrCheckResponse> {
+        unsupported()
+    }
+
+    fn set_cookie(&mut self, _cookie: String) {}
+
+    fn cookie(&self) -> Option<&str> {
+        None
+    }
+
+    async fn get_user_profile(&self, _uid: i64) -> Result<UserProfile> {
+        unsupported()
+    }
+
+    async fn get_user_record(&self, _uid: i64) -> Result<UserRecord> {
+        unsupported()
+    }
+
+    async fn get_user_playlists(&self, _uid: i64, _limit: Option<i32>, _offset: Option<i32>) -> Result<PlaylistResponse> {
+        unsupported()
+    }
+
+    async fn get_user_follows(&self, uid: i64, _limit: Option<i32>, _offset: Option<i32>) -> Result<FollowsResponse> {
+        Ok(FollowsResponse {
+            code: 200,
+            follow: vec![follow(uid + 1)],
+            more: false,
+        })
+    }
+
+    async fn get_user_followeds(&self, _uid: i64, _limit: Option<i32>, _offset: Option<i32>) -> Result<FollowedsResponse> {
+        Ok(FollowedsResponse {
+            code: 200,
+            followeds: Vec::new(),
+            more: false,
+            lasttime: None,
+        })
+    }
+
+    async fn get_playlist_tracks(&self, _playlist_id: i64, _limit: Option<i32>, _offset: Option<i32>) -> Result<PlaylistTracksResponse> {
+        unsupported()
+    }
+
+    async fn get_song_detail(&self, _ids: &[i64]) -> Result<SongDetailResponse> {
+        unsupported()
+    }
+
+    async fn get_hot_comments(&self, _resource: ResourceType, _id: i64, _limit: i32, _offset: i32) -> Result<HotCommentsResponse> {
+        unsupported()
+    }
+
+    async fn get_song_comments(&self, _song_id: i64, limit: i32, offset: i32, _before: Option<i64>) -> Result<CommentResponse> {
+        let comments = (offset..(offset + limit).min(150))
+            .map(|id| Comment {
+                comment_id: id as i64,
+                user: CommentUser {
+                    user_id: if id == 10 || id == 140 { 7 } else { 100 + id as i64 },
+                    nickname: String::new(),
+                    avatar_url: String::new(),
+                },
+                content: format!("comment {}", id),
+                time: 1_700_000_000_000i64 - id as i64,
+                liked_count: 0,
+                be_replied: Vec::new(),
+            })
+            .collect();
+        Ok(CommentResponse {
+            code: 200,
+            comments,
+            hot_comments: Vec::new(),
+            total: 150,
+            more: None,
+        })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn crawlers_run_against_any_music_api() {
+    let api: Box<dyn MusicApi> = Box::new(InMemoryApi);
+
+    let scan = api.scan_song_for_user(1, 7, &ProgressBar::hidden()).await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 150);
+    assert_eq!(scan.matched.len(), 2);
+
+    let options = GraphCrawlOptions {
+        depth: 3,
+        max_nodes: 100,
+        max_neighbors: 10,
+        direction: Direction::Follows,
+        concurrency: 2,
+    };
+    let graph = api.crawl_social_graph(&[1], &options).await.unwrap();
+    assert_eq!(graph.nodes.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    assert_eq!(graph.edges.len(), 3);
+}
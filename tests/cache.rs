@@ -0,0 +1,117 @@
+use netease_music_api::cache::{CachingTransport, ResponseCache};
+use netease_music_api::{MockTransport, MusicApi, NeteaseMusicClient};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn profile_body(nickname: &str) -> serde_json::Value {
+    json!({
+        "code": 200,
+        "profile": { "nickname": nickname, "userId": 1, "avatarUrl": "", "createTime": 0 }
+    })
+}
+
+#[tokio::test]
+async fn serves_repeated_requests_from_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/user/detail", profile_body("缓存用户"));
+    mock.push_json("/login/qr/key", json!({ "code": 200, "data": { "unikey": "k" } }));
+
+    let cache = ResponseCache::new(dir.path());
+    let client = NeteaseMusicClient::with_transport(Arc::new(CachingTransport::new(mock.clone(), cache.clone())));
+
+    for _ in 0..3 {
+        assert_eq!(client.get_user_profile(1).await.unwrap().profile.nickname, "缓存用户");
+    }
+    assert_eq!(mock.requests_to("/user/detail").len(), 1);
+
+    // 未配置缓存时间的接口（带 timestamp 的登录接口）每次都会请求
+    client.get_qr_key().await.unwrap();
+    client.get_qr_key().await.unwrap();
+    assert_eq!(mock.requests_to("/login/qr/key").len(), 2);
+
+    let stats = cache.stats().unwrap();
+    assert_eq!(stats["/user/detail"].entries, 1);
+    assert!(!stats.contains_key("/login/qr/key"));
+    // 写入经过临时文件改名，不留下 .part 文件
+    let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].extension().is_some_and(|ext| ext == "json"));
+}
+
+#[tokio::test]
+async fn does_not_cache_error_responses() {
+    let dir = tempfile::tempdir().unwrap();
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/user/detail", json!({ "code": 405, "message": "操作频繁" }));
+    mock.push_json("/user/detail", profile_body("重试成功"));
+
+    let cache = ResponseCache::new(dir.path());
+    let client = NeteaseMusicClient::with_transport(Arc::new(CachingTransport::new(mock.clone(), cache)));
+
+    assert!(client.get_user_profile(1).await.is_err());
+    assert_eq!(client.get_user_profile(1).await.unwrap().profile.nickname, "重试成功");
+    assert_eq!(mock.requests_to("/user/detail").len(), 2);
+}
+
+#[tokio::test]
+async fn offline_mode_ignores_ttl_and_fails_on_miss() {
+    let dir = tempfile::tempdir().unwrap();
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/user/detail", profile_body("离线用户"));
+
+    let cache = ResponseCache::new(dir.path());
+    let online = NeteaseMusicClient::with_transport(Arc::new(CachingTransport::new(mock.clone(), cache)));
+    online.get_user_profile(1).await.unwrap();
+
+    // 缓存时间为 0 时条目视为过期，离线模式仍然返回
+    let mut cache = ResponseCache::new(dir.path());
+    cache.set_ttl("/user/detail", Duration::ZERO);
+    let offline = NeteaseMusicClient::with_transport(Arc::new(CachingTransport::offline(cache.clone())));
+    assert_eq!(offline.get_user_profile(1).await.unwrap().profile.nickname, "离线用户");
+    assert!(offline.get_user_profile(2).await.is_err());
+
+    assert_eq!(cache.purge(true, Some("/user/record")).unwrap(), 0);
+    assert_eq!(cache.purge(true, None).unwrap(), 1);
+    assert!(offline.get_user_profile(1).await.is_err());
+}
+
+#[tokio::test]
+async fn keys_login_dependent_responses_by_account() {
+    let dir = tempfile::tempdir().unwrap();
+    let mock = Arc::new(MockTransport::new());
+    let record = |name: &str| json!({ "code": 200, "allData": [{ "score": 1, "song": { "name": name, "id": 1 } }] });
+    mock.push_json("/user/record", record("账号 A"));
+    mock.push_json("/user/record", record("账号 B"));
+    mock.push_json("/user/record", record("未登录"));
+
+    let mut cache = ResponseCache::new(dir.path());
+    // /login/ 下的接口即使设置了缓存时间也不缓存
+    cache.set_ttl("/login/qr/check", Duration::from_secs(60));
+    let transport = Arc::new(CachingTransport::new(mock.clone(), cache));
+    let client = |cookie: Option<&str>| {
+        let mut client = NeteaseMusicClient::with_transport(transport.clone());
+        if let Some(cookie) = cookie {
+            client.set_cookie(cookie.to_string());
+        }
+        client
+    };
+    let song = |client: NeteaseMusicClient| async move {
+        client.get_user_record(1).await.unwrap().all_data[0].song.name.clone()
+    };
+
+    assert_eq!(song(client(Some("MUSIC_U=a; __csrf=x"))).await, "账号 A");
+    assert_eq!(song(client(Some("MUSIC_U=b"))).await, "账号 B");
+    assert_eq!(song(client(None)).await, "未登录");
+    // 同一账号的 cookie 其他部分变化时仍命中缓存
+    assert_eq!(song(client(Some("__csrf=y; MUSIC_U=a"))).await, "账号 A");
+    assert_eq!(mock.requests_to("/user/record").len(), 3);
+
+    mock.push_json("/login/qr/check", json!({ "code": 801, "message": "等待扫码" }));
+    mock.push_json("/login/qr/check", json!({ "code": 801, "message": "等待扫码" }));
+    let anonymous = client(None);
+    anonymous.check_qr("k").await.unwrap();
+    anonymous.check_qr("k").await.unwrap();
+    assert_eq!(mock.requests_to("/login/qr/check").len(), 2);
+}
@@ -0,0 +1,85 @@
+use netease_music_api::cassette::{RecordingTransport, ReplayTransport};
+use netease_music_api::{HttpResponse, MockTransport, MusicApi, NeteaseMusicClient};
+use serde_json::json;
+use std::fs;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn records_redacted_cassette_and_replays_it() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let mock = Arc::new(MockTransport::new());
+    mock.push(
+        "/login/cellphone",
+        HttpResponse {
+            status: 200,
+            set_cookies: vec!["MUSIC_U=secret-value; Path=/".to_string()],
+            body: json!({ "code": 200, "token": "secret-token", "cookie": "MUSIC_U=secret-value" })
+                .to_string()
+                .into_bytes(),
+        },
+    );
+    mock.push_json(
+        "/user/detail",
+        json!({
+            "code": 200,
+            "profile": { "nickname": "录制用户", "userId": 1, "avatarUrl": "", "createTime": 0 }
+        }),
+    );
+
+    let recorder = RecordingTransport::new(mock.clone(), dir.path()).unwrap();
+    let mut client = NeteaseMusicClient::with_transport(Arc::new(recorder));
+    client.login_cellphone("13800000000", "hunter2").await.unwrap();
+    client.get_user_profile(1).await.unwrap();
+
+    let stored: String = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| fs::read_to_string(e.unwrap().path()).unwrap())
+        .collect();
+    for secret in ["secret-value", "secret-token", "hunter2", "13800000000"] {
+        assert!(!stored.contains(secret), "cassette leaks {}", secret);
+    }
+    assert!(stored.contains("MUSIC_U=REDACTED; Path=/"));
+
+    let replay = ReplayTransport::load(dir.path()).unwrap();
+    let client = NeteaseMusicClient::with_transport(Arc::new(replay));
+    let profile = client.get_user_profile(1).await.unwrap();
+    assert_eq!(profile.profile.nickname, "录制用户");
+    assert!(client.get_user_profile(2).await.is_err());
+}
+
+#[tokio::test]
+async fn numbers_and_replays_interactions_by_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let interaction = |nickname: &str| {
+        json!({
+            "request": { "path": "/user/detail", "query": [["uid", "1"]], "cookie": null },
+            "response": {
+                "status": 200,
+                "set_cookies": [],
+                "body": {
+                    "code": 200,
+                    "profile": { "nickname": nickname, "userId": 1, "avatarUrl": "", "createTime": 0 }
+                }
+            }
+        })
+        .to_string()
+    };
+    // 编号超过五位后按数值排序，其他文件既不参与编号也不参与回放
+    fs::write(dir.path().join("99999_user_detail.json"), interaction("第一次")).unwrap();
+    fs::write(dir.path().join("100000_user_detail.json"), interaction("第二次")).unwrap();
+    fs::write(dir.path().join("README.md"), "录制说明").unwrap();
+    fs::write(dir.path().join("notes.json"), "{}").unwrap();
+
+    let replay = ReplayTransport::load(dir.path()).unwrap();
+    let client = NeteaseMusicClient::with_transport(Arc::new(replay));
+    assert_eq!(client.get_user_profile(1).await.unwrap().profile.nickname, "第一次");
+    assert_eq!(client.get_user_profile(1).await.unwrap().profile.nickname, "第二次");
+
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/user/record", json!({ "code": 200, "allData": [] }));
+    let recorder = RecordingTransport::new(mock, dir.path()).unwrap();
+    let client = NeteaseMusicClient::with_transport(Arc::new(recorder));
+    client.get_user_record(1).await.unwrap();
+    assert!(dir.path().join("100001_user_record.json").exists());
+}
@@ -0,0 +1,434 @@
+use futures::StreamExt;
+use indicatif::ProgressBar;
+use netease_music_api::comment_stream::PageCursor;
+use netease_music_api::metadata::{self, SONG_DETAIL_BATCH};
+use netease_music_api::{
+    CommentMatcher, CommentStreamExt, CommentStreamOptions, Filter, HttpResponse, MentionKind, MentionTarget,
+    MockTransport, MusicApi, NeteaseMusicClient, ResourceType, Song, SongCrawler,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+fn comment(id: i64, user_id: i64) -> serde_json::Value {
+    json!({
+        "commentId": id,
+        "user": { "userId": user_id, "nickname": format!("user{}", user_id), "avatarUrl": "" },
+        "content": format!("comment {}", id),
+        "time": 1_700_000_000_000i64 - id,
+        "likedCount": 0
+    })
+}
+
+fn comment_page(ids: std::ops::Range<i64>, user_id: impl Fn(i64) -> i64, more: bool) -> serde_json::Value {
+    let comments: Vec<_> = ids.map(|id| comment(id, user_id(id))).collect();
+    json!({ "code": 200, "comments": comments, "total": 130, "more": more })
+}
+
+fn client_with(mock: &Arc<MockTransport>) -> NeteaseMusicClient {
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    client.set_cookie("MUSIC_U=test".to_string());
+    client
+}
+
+#[tokio::test]
+async fn login_stores_cookie_and_sends_it_afterwards() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push(
+        "/login/cellphone",
+        HttpResponse {
+            status: 200,
+            set_cookies: vec!["MUSIC_U=abc".to_string(), "__csrf=def".to_string()],
+            body: json!({ "code": 200, "account": null, "profile": null }).to_string().into_bytes(),
+        },
+    );
+    mock.push_json("/user/record", json!({ "code": 200, "allData": [] }));
+
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    let login = client.login_cellphone("13800000000", "secret").await.unwrap();
+    assert_eq!(login.cookie, "MUSIC_U=abc; __csrf=def");
+    assert_eq!(client.cookie(), Some("MUSIC_U=abc; __csrf=def"));
+
+    client.get_user_record(1).await.unwrap();
+    let record_requests = mock.requests_to("/user/record");
+    assert_eq!(record_requests[0].cookie.as_deref(), Some("MUSIC_U=abc; __csrf=def"));
+}
+
+#[tokio::test]
+async fn login_rejects_non_200_code() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/login/cellphone", json!({ "code": 502 }));
+
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    assert!(client.login_cellphone("13800000000", "wrong").await.is_err());
+    assert_eq!(client.cookie(), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn qr_polling_walks_through_states_until_confirmed() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/login/qr/check", json!({ "code": 801, "message": "等待扫码" }));
+    mock.push_json("/login/qr/check", json!({ "code": 802, "message": "待确认" }));
+    mock.push_json(
+        "/login/qr/check",
+        json!({ "code": 803, "message": "授权登陆成功", "cookie": "MUSIC_U=qr" }),
+    );
+
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    let cookie = client.poll_qr_login("key").await.unwrap();
+    assert_eq!(cookie, "MUSIC_U=qr");
+    assert_eq!(client.cookie(), Some("MUSIC_U=qr"));
+
+    let checks = mock.requests_to("/login/qr/check");
+    assert_eq!(checks.len(), 3);
+    assert_eq!(checks[0].query_value("key"), Some("key"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn qr_polling_fails_when_code_expires() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/login/qr/check", json!({ "code": 801, "message": "等待扫码" }));
+    mock.push_json("/login/qr/check", json!({ "code": 800, "message": "二维码不存在或已过期" }));
+
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    assert!(client.poll_qr_login("key").await.is_err());
+    assert_eq!(client.cookie(), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn comment_stream_stops_after_short_page() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(0..100, |_| 1, true));
+    mock.push_json("/comment/music", comment_page(100..130, |_| 1, true));
+
+    let client = client_with(&mock);
+    let comments: Vec<_> = client
+        .comment_stream(42, CommentStreamOptions::default())
+        .collect()
+        .await;
+    assert_eq!(comments.len(), 130);
+    assert!(comments.iter().all(|c| c.is_ok()));
+
+    let requests = mock.requests_to("/comment/music");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].query_value("offset"), Some("0"));
+    assert_eq!(requests[1].query_value("offset"), Some("100"));
+    assert_eq!(requests[1].query_value("id"), Some("42"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn comment_stream_stops_when_api_reports_no_more() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(0..100, |_| 1, false));
+
+    let client = client_with(&mock);
+    let comments: Vec<_> = client
+        .comment_stream(42, CommentStreamOptions::default())
+        .collect()
+        .await;
+    assert_eq!(comments.len(), 100);
+    assert_eq!(mock.requests_to("/comment/music").len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn comment_stream_retries_then_yields_error() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", json!({ "code": 405, "message": "操作频繁" }));
+
+    let client = client_with(&mock);
+    let options = CommentStreamOptions {
+        max_retries: 2,
+        ..CommentStreamOptions::default()
+    };
+    let comments: Vec<_> = client.comment_stream(42, options).collect().await;
+    assert_eq!(comments.len(), 1);
+    assert!(comments[0].is_err());
+    assert_eq!(mock.requests_to("/comment/music").len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_keeps_only_target_user_comments() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json(
+        "/comment/music",
+        comment_page(0..100, |id| if id % 10 == 0 { 7 } else { 1 }, true),
+    );
+    mock.push_json("/comment/music", comment_page(100..105, |id| if id == 104 { 7 } else { 1 }, true));
+
+    let client = client_with(&mock);
+    let scan = client.scan_song_for_user(42, 7, &ProgressBar::hidden()).await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 105);
+    assert_eq!(scan.matched.len(), 11);
+    assert_eq!(scan.pages, 2);
+    assert_eq!(scan.requests, 2);
+    assert!(scan.matched.iter().all(|c| c.用户.用户ID == 7));
+}
+
+// 收集日志输出的 writer
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "current_thread", start_paused = true)]
+async fn requests_are_logged_with_song_and_offset() {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(0..3, |_| 1, false));
+    mock.push_json("/comment/album", comment_page(0..3, |_| 1, false));
+    mock.push_json("/comment/event", comment_page(0..3, |_| 1, false));
+    let client = client_with(&mock);
+    client.scan_song_for_user(42, 7, &ProgressBar::hidden()).await;
+    let matcher = CommentMatcher::users(&[7]);
+    for resource in [ResourceType::Album, ResourceType::Event { owner: 9 }] {
+        client
+            .scan_comments_with(resource, 43, &matcher, PageCursor::Offset(0), 1000, &ProgressBar::hidden(), &mut |_| Ok(()))
+            .await;
+    }
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let events: Vec<serde_json::Value> = output.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    let requests: Vec<_> = events.iter().filter(|e| e["fields"]["message"] == "请求完成").collect();
+    assert_eq!(requests.len(), 3, "缺少请求日志");
+    let request = requests[0];
+    assert_eq!(request["fields"]["status"], 200);
+    assert_eq!(request["fields"]["code"], 200);
+    assert_eq!(request["span"]["song_id"], "42");
+    assert_eq!(request["span"]["resource_id"], "42");
+    assert_eq!(request["span"]["offset"], "0");
+    assert_eq!(request["spans"][0]["name"], "song");
+    assert_eq!(request["spans"][0]["target_uids"], "[7]");
+
+    // 其他资源的评论记录 resource_id，不记录 song_id
+    let album = requests[1];
+    assert_eq!(album["span"]["path"], "/comment/album");
+    assert_eq!(album["span"]["resource_id"], "43");
+    assert!(album["span"].get("song_id").is_none());
+    assert_eq!(album["spans"][0]["name"], "comments");
+    assert_eq!(album["spans"][0]["resource"], "album");
+    let event = requests[2];
+    assert_eq!(event["span"]["resource_id"], "A_EV_2_43_9");
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_counts_retried_requests_and_keeps_error() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(0..100, |_| 1, true));
+    mock.push_json("/comment/music", json!({ "code": 405, "message": "操作频繁" }));
+
+    let client = client_with(&mock);
+    let scan = client.scan_song_for_user(42, 7, &ProgressBar::hidden()).await;
+    assert_eq!(scan.scanned, 100);
+    assert_eq!(scan.pages, 1);
+    // 第二页首次请求加 3 次重试
+    assert_eq!(scan.requests, 5);
+    assert!(scan.error.is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_records_failed_cursor_and_resumes_from_it() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(0..100, |_| 1, true));
+    mock.push_json("/comment/music", json!({ "code": 405, "message": "操作频繁" }));
+
+    let client = client_with(&mock);
+    let scan = client.scan_song_for_user(42, 7, &ProgressBar::hidden()).await;
+    assert!(scan.error.is_some());
+    assert_eq!(scan.failed_at, Some(PageCursor::Offset(100)));
+
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(100..130, |id| if id == 120 { 7 } else { 1 }, true));
+    let client = client_with(&mock);
+    let scan = client
+        .resume_song_scan(42, &[7], PageCursor::Offset(100), 9900, &ProgressBar::hidden())
+        .await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 30);
+    assert_eq!(scan.matched.len(), 1);
+    let requests = mock.requests_to("/comment/music");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].query_value("offset"), Some("100"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_sizes_progress_bar_from_comment_total() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(0..100, |_| 1, true));
+    mock.push_json("/comment/music", comment_page(100..130, |_| 1, true));
+
+    let client = client_with(&mock);
+    let bar = ProgressBar::hidden();
+    client.scan_song_for_user(42, 7, &bar).await;
+    assert_eq!(bar.length(), Some(130));
+    assert_eq!(bar.position(), 130);
+
+    // 续抓时扣除已跳过的评论
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(100..130, |_| 1, true));
+    let client = client_with(&mock);
+    let bar = ProgressBar::hidden();
+    client
+        .resume_song_scan(42, &[7], PageCursor::Offset(100), 9900, &bar)
+        .await;
+    assert_eq!(bar.length(), Some(30));
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_matches_several_users_in_one_pass() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json(
+        "/comment/music",
+        comment_page(0..100, |id| match id % 10 { 0 => 7, 5 => 8, _ => 1 }, true),
+    );
+    mock.push_json("/comment/music", comment_page(100..130, |id| if id == 120 { 9 } else { 1 }, true));
+
+    let client = client_with(&mock);
+    let scan = client.scan_song_for_users(42, &[7, 8, 9], &ProgressBar::hidden()).await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 130);
+    let count = |uid| scan.matched.iter().filter(|c| c.用户.用户ID == uid).count();
+    assert_eq!((count(7), count(8), count(9)), (10, 10, 1));
+    // 多个目标用户也只翻一遍
+    assert_eq!(mock.requests_to("/comment/music").len(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_applies_filter_on_top_of_target_users() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json(
+        "/comment/music",
+        comment_page(0..100, |id| match id % 10 { 0 => 7, 5 => 8, _ => 1 }, false),
+    );
+
+    let client = client_with(&mock);
+    let matcher = CommentMatcher {
+        filter: Some(Filter::parse(r#"time>1699999999950 && !nickname~"8""#).unwrap()),
+        ..CommentMatcher::users(&[7, 8])
+    };
+    let mut matched = Vec::new();
+    let scan = client
+        .scan_song_with(42, &matcher, PageCursor::Offset(0), 1000, &ProgressBar::hidden(), &mut |m| {
+            matched.push(m.comment.评论ID);
+            Ok(())
+        })
+        .await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 100);
+    assert_eq!(matched, vec![0, 10, 20, 30, 40]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn scan_finds_replies_and_at_mentions_of_targets() {
+    let mut comments: Vec<_> = (0..5).map(|id| comment(id, 1)).collect();
+    comments[1]["user"]["userId"] = json!(7);
+    comments[2]["content"] = json!("@小猫 晚安");
+    comments[3]["beReplied"] = json!([{ "user": { "userId": 7, "nickname": "小猫" }, "beRepliedCommentId": 1 }]);
+    comments[4]["content"] = json!("@小猫咪 早");
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", json!({ "code": 200, "comments": comments, "total": 5, "more": false }));
+
+    let client = client_with(&mock);
+    let matcher = CommentMatcher {
+        mentions: vec![MentionTarget::new(7, ["小猫".to_string()])],
+        ..CommentMatcher::users(&[7])
+    };
+    let mut matched = Vec::new();
+    let scan = client
+        .scan_song_with(42, &matcher, PageCursor::Offset(0), 1000, &ProgressBar::hidden(), &mut |m| {
+            matched.push((m.comment.评论ID, m.uid, m.mention));
+            Ok(())
+        })
+        .await;
+    assert!(scan.error.is_none());
+    assert_eq!(
+        matched,
+        vec![(1, 7, None), (2, 7, Some(MentionKind::At)), (3, 7, Some(MentionKind::Reply))]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn scans_comments_of_other_resources_by_their_endpoint() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/album", comment_page(0..30, |id| if id == 3 { 7 } else { 1 }, false));
+    mock.push_json("/comment/event", comment_page(0..30, |id| if id < 2 { 7 } else { 1 }, false));
+
+    let client = client_with(&mock);
+    let matcher = CommentMatcher::users(&[7]);
+    let mut matched = Vec::new();
+    let album = client
+        .scan_comments_with(ResourceType::Album, 32311, &matcher, PageCursor::Offset(0), 1000, &ProgressBar::hidden(), &mut |m| {
+            matched.push(m.comment.评论ID);
+            Ok(())
+        })
+        .await;
+    assert!(album.error.is_none());
+    assert_eq!(matched, vec![3]);
+    assert_eq!(mock.requests_to("/comment/album")[0].query_value("id"), Some("32311"));
+
+    // 动态的评论区按 threadId 请求
+    let event = ResourceType::Event { owner: 32953014 };
+    let scan = client
+        .scan_comments_with(event, 6559519868, &matcher, PageCursor::Offset(0), 1000, &ProgressBar::hidden(), &mut |_| Ok(()))
+        .await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 30);
+    let request = &mock.requests_to("/comment/event")[0];
+    assert_eq!(request.query_value("threadId"), Some("A_EV_2_6559519868_32953014"));
+    assert_eq!(request.query_value("id"), None);
+    assert!(mock.requests_to("/comment/music").is_empty());
+}
+
+#[tokio::test]
+async fn enriches_bare_songs_in_batches() {
+    let detail = |id: i64| {
+        json!({
+            "name": format!("song{}", id),
+            "id": id,
+            "ar": [{ "id": 1, "name": "歌手" }],
+            "al": { "id": 2, "name": "专辑" },
+            "dt": 200000,
+            "publishTime": 0,
+            "alia": ["别名"]
+        })
+    };
+    let first: Vec<_> = (0..SONG_DETAIL_BATCH as i64).map(detail).collect();
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/song/detail", json!({ "code": 200, "songs": first }));
+    // 第二批里 1001 已下架，接口不返回
+    mock.push_json("/song/detail", json!({ "code": 200, "songs": [detail(1000)] }));
+
+    let mut songs: Vec<Song> = (0..SONG_DETAIL_BATCH as i64 + 2).map(|id| Song::new(id, id.to_string())).collect();
+    songs.push(serde_json::from_value(detail(5000)).unwrap());
+    let client = client_with(&mock);
+    let enriched = metadata::enrich_songs(&client, &mut songs).await.unwrap();
+    assert_eq!(enriched, SONG_DETAIL_BATCH + 1);
+
+    let requests = mock.requests_to("/song/detail");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].query_value("ids").unwrap().split(',').count(), SONG_DETAIL_BATCH);
+    assert_eq!(requests[1].query_value("ids"), Some("1000,1001"));
+    assert_eq!(songs[3].name, "song3");
+    assert_eq!(songs[3].artist_names(), "歌手");
+    assert_eq!(songs[3].alias, vec!["别名"]);
+    assert!(songs[1001].needs_details());
+    assert_eq!(songs[1001].name, "1001");
+}
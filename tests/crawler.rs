@@ -0,0 +1,55 @@
+use netease_music_api::retry::RETRY_QUEUE_PATH;
+use netease_music_api::{
+    CrawlOptions, MockTransport, MusicApi, NeteaseMusicClient, ProgressMode, ResourceType, RetryQueue, SongCrawler,
+};
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+
+// 批量抓取写入当前目录下的 comments/，这个文件里的测试共用一个临时工作目录，只放这一个测试
+#[tokio::test(start_paused = true)]
+async fn retry_queue_accumulates_attempts_across_crawls() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", json!({ "code": 405, "message": "操作频繁" }));
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    client.set_cookie("MUSIC_U=test".to_string());
+    let options = CrawlOptions {
+        progress: ProgressMode::Quiet,
+        ..CrawlOptions::default()
+    };
+    let queue_path = Path::new(RETRY_QUEUE_PATH);
+
+    // 同一首歌两次完整抓取都失败，失败次数累加而不是被新抓取重置
+    for _ in 0..2 {
+        client
+            .get_user_comments_for_resources(&[(ResourceType::Song, 42)], &[7], &options)
+            .await
+            .unwrap();
+    }
+    let queue = RetryQueue::load(queue_path).unwrap();
+    assert_eq!(queue.entries.len(), 1);
+    assert_eq!(queue.entries[0].attempts, 2);
+
+    client.retry_failed_songs(queue_path, &options).await.unwrap();
+    let queue = RetryQueue::load(queue_path).unwrap();
+    assert_eq!(queue.entries[0].attempts, 3);
+
+    // 成功后移出队列
+    let comment = json!({
+        "commentId": 1,
+        "user": { "userId": 7, "nickname": "七", "avatarUrl": "" },
+        "content": "晚安",
+        "time": 1_700_000_000_000i64,
+        "likedCount": 0
+    });
+    mock.push_json("/comment/music", json!({ "code": 200, "comments": [comment], "total": 1, "more": false }));
+    let report = client.retry_failed_songs(queue_path, &options).await.unwrap();
+    assert_eq!(report.songs[0].matched, 1);
+    assert!(RetryQueue::load(queue_path).unwrap().is_empty());
+    // 不查找提及时只写评论文件，不记录昵称
+    assert!(Path::new("comments/user_7/song_42.json").exists());
+    assert!(!Path::new("comments/user_7/nicknames.json").exists());
+}
@@ -0,0 +1,72 @@
+use netease_music_api::{DirectTransport, HttpConfig, MusicApi, NeteaseMusicClient};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// 只接受一个连接，读完请求后返回 body，并把收到的原始请求交给调用方
+async fn serve_once(listener: TcpListener, body: &'static str) -> String {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut raw = Vec::new();
+    let mut buf = vec![0; 8192];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        raw.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&raw).to_lowercase();
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length: "))
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if raw.len() >= end + 4 + length {
+                break;
+            }
+        }
+        if n == 0 {
+            break;
+        }
+    }
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await.unwrap();
+    String::from_utf8_lossy(&raw).into_owned()
+}
+
+async fn direct_client(body: &'static str) -> (NeteaseMusicClient, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(serve_once(listener, body));
+    let transport = DirectTransport::with_config(&HttpConfig {
+        base_url: format!("http://{}", addr),
+        real_ip: Some("116.25.146.177".to_string()),
+        ..HttpConfig::default()
+    })
+    .unwrap();
+    (NeteaseMusicClient::with_transport(Arc::new(transport)), server)
+}
+
+#[tokio::test]
+async fn comments_are_posted_to_weapi_endpoint() {
+    let (client, server) = direct_client(r#"{"code":200,"comments":[],"total":0,"more":false}"#).await;
+    let page = client.get_song_comments(186016, 20, 0, None).await.unwrap();
+    assert_eq!(page.code, 200);
+
+    let raw = server.await.unwrap();
+    assert!(raw.starts_with("POST /weapi/v1/resource/comments/R_SO_4_186016 "), "{}", raw);
+    let lower = raw.to_lowercase();
+    assert!(lower.contains("x-real-ip: 116.25.146.177"));
+    assert!(lower.contains("user-agent: mozilla/5.0"));
+    let form = raw.split("\r\n\r\n").nth(1).unwrap();
+    assert!(form.starts_with("params="));
+    assert!(form.contains("&encSecKey="));
+}
+
+#[tokio::test]
+async fn qr_key_response_is_adapted() {
+    let (client, server) = direct_client(r#"{"code":200,"unikey":"direct-key"}"#).await;
+    assert_eq!(client.get_qr_key().await.unwrap(), "direct-key");
+    assert!(server.await.unwrap().starts_with("POST /weapi/login/qrcode/unikey "));
+}
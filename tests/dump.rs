@@ -0,0 +1,139 @@
+use netease_music_api::dump::{self, NdjsonSink};
+use netease_music_api::{CommentDumper, CrawlOptions, Filter, MockTransport, MusicApi, NeteaseMusicClient, ProgressMode, Song};
+use serde_json::json;
+use std::sync::Arc;
+
+fn comment_page(song_id: i64, ids: std::ops::Range<i64>) -> serde_json::Value {
+    let comments: Vec<_> = ids
+        .map(|id| {
+            json!({
+                "commentId": song_id * 1000 + id,
+                "user": { "userId": id, "nickname": format!("user{}", id), "avatarUrl": "" },
+                "content": format!("comment {}", id),
+                "time": 1_700_000_000_000i64 - id,
+                "likedCount": id
+            })
+        })
+        .collect();
+    json!({ "code": 200, "comments": comments, "total": 30, "more": false })
+}
+
+fn quiet() -> CrawlOptions {
+    CrawlOptions {
+        progress: ProgressMode::Quiet,
+        ..CrawlOptions::default()
+    }
+}
+
+fn client_with(mock: &Arc<MockTransport>) -> NeteaseMusicClient {
+    let mut client = NeteaseMusicClient::with_transport(mock.clone());
+    client.set_cookie("MUSIC_U=test".to_string());
+    client
+}
+
+#[tokio::test(start_paused = true)]
+async fn dumps_every_comment_to_ndjson() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(1, 0..30));
+    mock.push_json("/comment/music", comment_page(1, 0..30));
+    let client = client_with(&mock);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dump.ndjson");
+    let songs = vec![Song::new(1, "a"), Song::new(2, "b")];
+    let report = client
+        .dump_song_comments(&songs, Box::new(NdjsonSink::create(&path).unwrap()), None, &quiet())
+        .await
+        .unwrap();
+    assert!(!report.has_failures());
+    assert_eq!(report.total_scanned(), 60);
+
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 60);
+    assert!(lines.iter().any(|l| l["song_id"] == 2 && l["user_id"] == 29 && l["liked_count"] == 29));
+}
+
+#[tokio::test(start_paused = true)]
+async fn dump_writes_only_comments_matching_filter() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/comment/music", comment_page(1, 0..30));
+    let client = client_with(&mock);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dump.ndjson");
+    let options = CrawlOptions {
+        filter: Some(Filter::parse("liked>=20 && content~/[05]$/").unwrap()),
+        ..quiet()
+    };
+    let report = client
+        .dump_song_comments(&[Song::new(1, "a")], Box::new(NdjsonSink::create(&path).unwrap()), None, &options)
+        .await
+        .unwrap();
+    assert_eq!(report.total_scanned(), 30);
+    assert_eq!(report.total_matched(), 2);
+
+    let users: Vec<i64> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["user_id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(users, vec![20, 25]);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test(start_paused = true)]
+async fn dumps_to_sqlite_and_deduplicates_on_rerun() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("dump.sqlite");
+    for limit in [None, Some(10)] {
+        let mock = Arc::new(MockTransport::new());
+        mock.push_json("/comment/music", comment_page(1, 0..30));
+        let client = client_with(&mock);
+        let sink = dump::open_sink(dump::DumpFormat::Sqlite, &path).unwrap();
+        client
+            .dump_song_comments(&[Song::new(1, "a")], sink, limit, &quiet())
+            .await
+            .unwrap();
+    }
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM comments", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 30);
+    let content: String = conn
+        .query_row("SELECT content FROM comments WHERE user_id = 7", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(content, "comment 7");
+}
+
+#[tokio::test]
+async fn reads_all_playlist_songs() {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json(
+        "/playlist/track/all",
+        json!({ "code": 200, "songs": [{ "id": 1, "name": "a" }, { "id": 2, "name": "b" }] }),
+    );
+    let client = client_with(&mock);
+    let songs = dump::playlist_songs(&client, 9).await.unwrap();
+    assert_eq!(songs.iter().map(|s| s.id).collect::<Vec<_>>(), vec![1, 2]);
+    let requests = mock.requests_to("/playlist/track/all");
+    assert_eq!(requests[0].query_value("id"), Some("9"));
+    assert_eq!(requests[0].query_value("offset"), Some("0"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_between_playlist_pages() {
+    let mock = Arc::new(MockTransport::new());
+    let full: Vec<_> = (0..1000).map(|id| json!({ "id": id, "name": id.to_string() })).collect();
+    mock.push_json("/playlist/track/all", json!({ "code": 200, "songs": full }));
+    mock.push_json("/playlist/track/all", json!({ "code": 200, "songs": [{ "id": 1000, "name": "last" }] }));
+    let client = client_with(&mock);
+    let started = tokio::time::Instant::now();
+    let songs = dump::playlist_songs(&client, 9).await.unwrap();
+    assert_eq!(songs.len(), 1001);
+    assert_eq!(mock.requests_to("/playlist/track/all")[1].query_value("offset"), Some("1000"));
+    assert!(started.elapsed() >= std::time::Duration::from_millis(netease_music_api::REQUEST_DELAY_MS));
+}
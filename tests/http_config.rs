@@ -0,0 +1,81 @@
+use netease_music_api::{HttpConfig, HttpRequest, ReqwestTransport, Transport};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+fn request() -> HttpRequest {
+    HttpRequest {
+        path: "/user/detail".to_string(),
+        query: vec![("uid".to_string(), "1".to_string())],
+        cookie: None,
+    }
+}
+
+// 只接受一个连接的 HTTP 服务，返回收到的原始请求
+async fn capture_one(listener: TcpListener) -> String {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut buf = vec![0; 4096];
+    let n = socket.read(&mut buf).await.unwrap();
+    let body = r#"{"code":200}"#;
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await.unwrap();
+    String::from_utf8_lossy(&buf[..n]).into_owned()
+}
+
+#[tokio::test]
+async fn sends_configured_headers_and_real_ip() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(capture_one(listener));
+
+    let transport = ReqwestTransport::with_config(&HttpConfig {
+        base_url: format!("http://{}", addr),
+        user_agent: Some("test-agent/1.0".to_string()),
+        headers: vec![("X-Custom".to_string(), "hello".to_string())],
+        real_ip: Some("116.25.146.177".to_string()),
+        ..HttpConfig::default()
+    })
+    .unwrap();
+    let response = transport.send(request()).await.unwrap();
+    assert_eq!(response.status, 200);
+
+    let raw = server.await.unwrap().to_lowercase();
+    assert!(raw.starts_with("get /user/detail?uid=1&realip=116.25.146.177 "), "{}", raw);
+    assert!(raw.contains("user-agent: test-agent/1.0"));
+    assert!(raw.contains("x-custom: hello"));
+}
+
+#[tokio::test]
+async fn request_timeout_aborts_slow_responses() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // 接受连接但不返回响应
+    let _server = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        drop(socket);
+    });
+
+    let transport = ReqwestTransport::with_config(&HttpConfig {
+        base_url: format!("http://{}", addr),
+        request_timeout: Some(Duration::from_millis(200)),
+        ..HttpConfig::default()
+    })
+    .unwrap();
+    let started = std::time::Instant::now();
+    assert!(transport.send(request()).await.is_err());
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn rejects_invalid_header_names() {
+    let config = HttpConfig {
+        headers: vec![("bad header".to_string(), "x".to_string())],
+        ..HttpConfig::default()
+    };
+    assert!(ReqwestTransport::with_config(&config).is_err());
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "mock-server")]
+
+use indicatif::ProgressBar;
+use netease_music_api::mock_server::{self, MockServerConfig};
+use netease_music_api::{CommentStreamExt, MusicApi, NeteaseMusicClient, ReqwestTransport, ResourceType, SongCrawler};
+use std::sync::Arc;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mock");
+
+async fn client_for(config: MockServerConfig) -> NeteaseMusicClient {
+    let addr = mock_server::spawn(config, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    let transport = ReqwestTransport::new(format!("http://{}", addr));
+    let mut client = NeteaseMusicClient::with_transport(Arc::new(transport));
+    client.set_cookie("MUSIC_U=mock".to_string());
+    client
+}
+
+#[tokio::test]
+async fn serves_user_endpoints_from_fixtures() {
+    let client = client_for(MockServerConfig::new(FIXTURES)).await;
+
+    let profile = client.get_user_profile(10001).await.unwrap();
+    assert_eq!(profile.profile.nickname, "模拟用户");
+
+    let record = client.get_user_record(10001).await.unwrap();
+    assert_eq!(record.all_data.len(), 3);
+
+    let follows = client.get_user_follows(10001, Some(2), Some(0)).await.unwrap();
+    assert_eq!(follows.follow.len(), 2);
+    assert!(follows.more);
+
+    let followeds = client.get_user_followeds(10001, None, None).await.unwrap();
+    assert_eq!(followeds.followeds.len(), 2);
+    assert!(!followeds.more);
+}
+
+#[tokio::test]
+async fn crawls_every_comment_page_end_to_end() {
+    let client = client_for(MockServerConfig::new(FIXTURES)).await;
+
+    let scan = client
+        .scan_song_for_user(186001, 10001, &ProgressBar::hidden())
+        .await;
+    assert!(scan.error.is_none());
+    assert_eq!(scan.scanned, 250);
+    assert_eq!(scan.matched.len(), 3);
+}
+
+#[tokio::test]
+async fn pages_hot_comments_until_has_more_is_false() {
+    let client = client_for(MockServerConfig::new(FIXTURES)).await;
+
+    let hot = client.hot_comments(ResourceType::Song, 186001, 100).await.unwrap();
+    assert_eq!(hot.len(), 30);
+    assert!(hot.windows(2).all(|w| w[0].liked_count >= w[1].liked_count));
+    assert!(hot.iter().any(|c| c.user.user_id == 10001));
+
+    let first = client.hot_comments(ResourceType::Album, 186001, 10).await.unwrap();
+    assert_eq!(first.len(), 10);
+    assert_eq!(first[0].comment_id, hot[0].comment_id);
+}
+
+#[tokio::test]
+async fn qr_check_fixture_is_served_in_sequence() {
+    let client = client_for(MockServerConfig::new(FIXTURES)).await;
+
+    let codes: Vec<i32> = futures::future::join_all((0..4).map(|_| client.check_qr("mock-unikey")))
+        .await
+        .into_iter()
+        .map(|r| r.unwrap().code)
+        .collect();
+    let mut sorted = codes.clone();
+    sorted.sort();
+    assert_eq!(sorted, vec![801, 802, 803, 803]);
+}
+
+#[tokio::test]
+async fn injects_errors_and_rate_limits() {
+    let config = MockServerConfig {
+        fail_every: Some(2),
+        error_code: 503,
+        ..MockServerConfig::new(FIXTURES)
+    };
+    let client = client_for(config).await;
+    assert_eq!(client.get_song_comments(186001, 10, 0, None).await.unwrap().code, 200);
+    assert_eq!(client.get_song_comments(186001, 10, 0, None).await.unwrap().code, 503);
+
+    let config = MockServerConfig {
+        rate_limit: Some(1),
+        ..MockServerConfig::new(FIXTURES)
+    };
+    let client = client_for(config).await;
+    assert_eq!(client.get_song_comments(186001, 10, 0, None).await.unwrap().code, 200);
+    assert_eq!(client.get_song_comments(186001, 10, 0, None).await.unwrap().code, 405);
+}
+
+#[tokio::test]
+async fn serves_song_comment_fixture_for_other_resources() {
+    let client = client_for(MockServerConfig::new(FIXTURES)).await;
+
+    let page = client.get_comments(ResourceType::Playlist, 24381616, 100, 0, None).await.unwrap();
+    assert_eq!(page.code, 200);
+    assert_eq!(page.comments.len(), 100);
+}
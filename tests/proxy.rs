@@ -0,0 +1,69 @@
+use netease_music_api::proxy::ProxyPoolTransport;
+use netease_music_api::{HttpRequest, MockTransport, Transport};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn request() -> HttpRequest {
+    HttpRequest {
+        path: "/user/detail".to_string(),
+        query: vec![("uid".to_string(), "1".to_string())],
+        cookie: None,
+    }
+}
+
+fn member(code: i64) -> Arc<MockTransport> {
+    let mock = Arc::new(MockTransport::new());
+    mock.push_json("/user/detail", json!({ "code": code }));
+    mock
+}
+
+fn pool(members: &[Arc<MockTransport>]) -> ProxyPoolTransport {
+    ProxyPoolTransport::from_transports(
+        members
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (format!("socks5://proxy{}", i), m.clone() as Arc<dyn Transport>))
+            .collect(),
+        Duration::from_secs(60),
+    )
+}
+
+#[tokio::test]
+async fn round_robins_across_proxies() {
+    let members = [member(200), member(200), member(200)];
+    let pool = pool(&members);
+    for _ in 0..6 {
+        pool.send(request()).await.unwrap();
+    }
+    for m in &members {
+        assert_eq!(m.requests().len(), 2);
+    }
+}
+
+#[tokio::test]
+async fn ejects_rate_limited_and_failing_proxies() {
+    // 第二个代理没有预设响应，相当于网络错误
+    let members = [member(405), Arc::new(MockTransport::new()), member(200)];
+    let pool = pool(&members);
+
+    let response = pool.send(request()).await.unwrap();
+    assert_eq!(response.body, json!({ "code": 200 }).to_string().into_bytes());
+    assert_eq!(pool.available(), 1);
+
+    for _ in 0..3 {
+        pool.send(request()).await.unwrap();
+    }
+    assert_eq!(members[0].requests().len(), 1);
+    assert_eq!(members[1].requests().len(), 1);
+    assert_eq!(members[2].requests().len(), 4);
+}
+
+#[tokio::test]
+async fn returns_last_failure_when_every_proxy_is_limited() {
+    let members = [member(405), member(-460)];
+    let pool = pool(&members);
+    let response = pool.send(request()).await.unwrap();
+    assert_eq!(response.body, json!({ "code": -460 }).to_string().into_bytes());
+    assert_eq!(pool.available(), 0);
+}